@@ -0,0 +1,840 @@
+// Copyright 2016 LambdaStack All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bucket configuration sub-resources (```?lifecycle```, ```?policy```, ```?website``` etc.) that are
+//! read with GET, replaced with PUT and removed with DELETE. Each command is
+//! ```get|set|rm s3://<bucket>``` where set takes a local file describing the configuration.
+//! Tagging also works on objects (```s3://<bucket>/<object>```).
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::collections::BTreeMap;
+
+use md5;
+use toml;
+use rustc_serialize::{Decodable, Encodable};
+use rustc_serialize::json;
+use rustc_serialize::json::Json;
+use rustc_serialize::base64::{STANDARD, ToBase64};
+use clap::ArgMatches;
+
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::s3::endpoint::Endpoint;
+use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+
+use common::*;
+use s3request;
+use headers::key_value;
+use s3request::{xml_elements, xml_escape, xml_unescape, xml_value, S3Request};
+
+use Client;
+use OutputFormat;
+
+/// Bucket lifecycle rules.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct LifecycleConfiguration {
+    pub rules: Vec<LifecycleRule>,
+}
+
+/// One lifecycle rule. Only the actions that are set are sent. ```status``` defaults to Enabled.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct LifecycleRule {
+    pub id: Option<String>,
+    pub prefix: Option<String>,
+    pub status: Option<String>,
+    pub expiration_days: Option<u64>,
+    pub noncurrent_version_expiration_days: Option<u64>,
+    pub abort_incomplete_multipart_upload_days: Option<u64>,
+}
+
+/// Bucket CORS rules.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct CorsConfiguration {
+    pub rules: Vec<CorsRule>,
+}
+
+/// One CORS rule. Each rule needs at least one origin and one method.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct CorsRule {
+    pub id: Option<String>,
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Option<Vec<String>>,
+    pub expose_headers: Option<Vec<String>>,
+    pub max_age_seconds: Option<u64>,
+}
+
+/// Tags of a bucket or object.
+///
+pub type TagSet = BTreeMap<String, String>;
+
+/// Bucket website hosting.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct WebsiteConfiguration {
+    pub index_document: String,
+    pub error_document: Option<String>,
+    pub routing_rules: Vec<RoutingRule>,
+}
+
+/// The ```--routing-rules``` file.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct RoutingRules {
+    pub rules: Vec<RoutingRule>,
+}
+
+/// Redirects requests that match the condition (or every request if there is no condition).
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct RoutingRule {
+    pub condition: Option<RoutingCondition>,
+    pub redirect: RoutingRedirect,
+}
+
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct RoutingCondition {
+    pub key_prefix_equals: Option<String>,
+    pub http_error_code_returned_equals: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct RoutingRedirect {
+    pub protocol: Option<String>,
+    pub host_name: Option<String>,
+    pub replace_key_prefix_with: Option<String>,
+    pub replace_key_with: Option<String>,
+    pub http_redirect_code: Option<String>,
+}
+
+/// Default server-side encryption of new objects in a bucket.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+pub struct EncryptionConfiguration {
+    pub sse_algorithm: String,
+    pub kms_master_key_id: Option<String>,
+}
+
+/// lifecycle get|set|rm
+pub fn lifecycle<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let config: LifecycleConfiguration = try!(read_config_file(matches.value_of("file").unwrap_or(""),
+                                                                       client));
+            let xml = try!(lifecycle_xml(&config).map_err(|e| config_error(e, client)));
+            put_config(bucket, "", "lifecycle", xml, client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, "", "lifecycle", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let body = try!(get_config(bucket, "", "lifecycle", "NoSuchLifecycleConfiguration", client));
+            let config = body.map(|body| lifecycle_from_xml(&body)).unwrap_or(LifecycleConfiguration::default());
+            print_config(&config, client);
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid lifecycle command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+/// policy get|set|rm
+pub fn policy<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let path = matches.value_of("file").unwrap_or("");
+            let policy = try!(read_file(path, client));
+            try!(validate_policy(&policy).map_err(|e| config_error(format!("{} is not a valid policy: {}", path, e),
+                                                                    client)));
+            put_config(bucket, "", "policy", policy, client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, "", "policy", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let body = try!(get_config(bucket, "", "policy", "NoSuchBucketPolicy", client));
+            let policy = body.and_then(|body| Json::from_str(&body).ok()).unwrap_or(Json::Object(BTreeMap::new()));
+            print_policy(&policy, client);
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid policy command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+// Catches the common mistakes locally instead of waiting for a MalformedPolicy from the server.
+fn validate_policy(policy: &str) -> Result<(), String> {
+    let json = try!(Json::from_str(policy).map_err(|e| format!("{}", e)));
+    let statements = match json.find("Statement") {
+        Some(&Json::Array(ref statements)) => statements.clone(),
+        Some(statement @ &Json::Object(_)) => vec![statement.clone()],
+        Some(_) => return Err("Statement must be an object or array".to_string()),
+        None => return Err("Statement is missing".to_string()),
+    };
+
+    if statements.is_empty() {
+        return Err("Statement is empty".to_string());
+    }
+
+    for (index, statement) in statements.iter().enumerate() {
+        if !statement.is_object() {
+            return Err(format!("Statement {} is not an object", index + 1));
+        }
+        for field in &["Effect", "Action", "Resource"] {
+            if statement.find(field).is_none() && statement.find(&format!("Not{}", field)).is_none() {
+                return Err(format!("Statement {} is missing {}", index + 1, field));
+            }
+        }
+        match statement.find("Effect").and_then(|effect| effect.as_string()) {
+            Some("Allow") | Some("Deny") => {},
+            _ => return Err(format!("Statement {} Effect must be Allow or Deny", index + 1)),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_policy<P, D>(policy: &Json, client: &Client<P, D>)
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match client.output.format {
+        OutputFormat::JSON => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{}", policy);
+        },
+        OutputFormat::None | OutputFormat::NoneAll => {},
+        _ => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{}", policy.pretty());
+        },
+    }
+}
+
+/// cors get|set|rm
+pub fn cors<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let config: CorsConfiguration = try!(read_config_file(matches.value_of("file").unwrap_or(""), client));
+            let xml = try!(cors_xml(&config).map_err(|e| config_error(e, client)));
+            put_config(bucket, "", "cors", xml, client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, "", "cors", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let body = try!(get_config(bucket, "", "cors", "NoSuchCORSConfiguration", client));
+            let config = body.map(|body| cors_from_xml(&body)).unwrap_or(CorsConfiguration::default());
+            print_config(&config, client);
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid cors command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+fn cors_xml(config: &CorsConfiguration) -> Result<String, String> {
+    if config.rules.is_empty() {
+        return Err("At least one rule is required (use `cors rm` to remove all rules)".to_string());
+    }
+
+    let mut xml = String::from("<CORSConfiguration>");
+
+    for (index, rule) in config.rules.iter().enumerate() {
+        if rule.allowed_origins.is_empty() || rule.allowed_methods.is_empty() {
+            return Err(format!("Rule {} needs at least one allowed origin and allowed method", index + 1));
+        }
+
+        xml.push_str("<CORSRule>");
+        if let Some(ref id) = rule.id {
+            xml.push_str(&format!("<ID>{}</ID>", xml_escape(id)));
+        }
+        for origin in &rule.allowed_origins {
+            xml.push_str(&format!("<AllowedOrigin>{}</AllowedOrigin>", xml_escape(origin)));
+        }
+        for method in &rule.allowed_methods {
+            let method = method.to_uppercase();
+            match method.as_ref() {
+                "GET" | "PUT" | "POST" | "DELETE" | "HEAD" => {},
+                _ => {
+                    return Err(format!("Rule {} has method {} (must be GET, PUT, POST, DELETE or HEAD)",
+                                       index + 1,
+                                       method))
+                },
+            }
+            xml.push_str(&format!("<AllowedMethod>{}</AllowedMethod>", method));
+        }
+        for header in rule.allowed_headers.as_ref().unwrap_or(&Vec::new()) {
+            xml.push_str(&format!("<AllowedHeader>{}</AllowedHeader>", xml_escape(header)));
+        }
+        for header in rule.expose_headers.as_ref().unwrap_or(&Vec::new()) {
+            xml.push_str(&format!("<ExposeHeader>{}</ExposeHeader>", xml_escape(header)));
+        }
+        if let Some(seconds) = rule.max_age_seconds {
+            xml.push_str(&format!("<MaxAgeSeconds>{}</MaxAgeSeconds>", seconds));
+        }
+        xml.push_str("</CORSRule>");
+    }
+
+    xml.push_str("</CORSConfiguration>");
+    Ok(xml)
+}
+
+fn cors_from_xml(xml: &str) -> CorsConfiguration {
+    let values = |rule: &str, tag: &str| -> Vec<String> {
+        xml_elements(rule, tag).into_iter().map(|value| xml_unescape(value)).collect()
+    };
+    let optional = |values: Vec<String>| if values.is_empty() { None } else { Some(values) };
+
+    CorsConfiguration {
+        rules: xml_elements(xml, "CORSRule")
+            .into_iter()
+            .map(|rule| {
+                CorsRule {
+                    id: xml_value(rule, "ID"),
+                    allowed_origins: values(rule, "AllowedOrigin"),
+                    allowed_methods: values(rule, "AllowedMethod"),
+                    allowed_headers: optional(values(rule, "AllowedHeader")),
+                    expose_headers: optional(values(rule, "ExposeHeader")),
+                    max_age_seconds: xml_value(rule, "MaxAgeSeconds").and_then(|seconds| seconds.parse::<u64>().ok()),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// tag get|set|rm for a bucket or an object
+pub fn tag<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, object, _) = find_bucket_object_last(&matches);
+            let mut tags = TagSet::new();
+            for value in matches.values_of("tags").map(|values| values.collect()).unwrap_or(Vec::new()) {
+                let (key, value) = try!(key_value(value).map_err(|e| config_error(e, client)));
+                tags.insert(key, value);
+            }
+            if tags.is_empty() {
+                return Err(config_error("At least one key=value tag is required (use `tag rm` to remove all tags)"
+                                            .to_string(),
+                                        client));
+            }
+            put_config(bucket, &object, "tagging", tagging_xml(&tags), client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, object, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, &object, "tagging", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, object, _) = find_bucket_object_last(&matches);
+            let body = try!(get_config(bucket, &object, "tagging", "NoSuchTagSet", client));
+            let tags = body.map(|body| tags_from_xml(&body)).unwrap_or(TagSet::new());
+            print_config(&tags, client);
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid tag command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+fn tagging_xml(tags: &TagSet) -> String {
+    let mut xml = String::from("<Tagging><TagSet>");
+
+    for (key, value) in tags {
+        xml.push_str(&format!("<Tag><Key>{}</Key><Value>{}</Value></Tag>", xml_escape(key), xml_escape(value)));
+    }
+
+    xml.push_str("</TagSet></Tagging>");
+    xml
+}
+
+/// The tags of an object (empty if it has none).
+pub fn object_tags(endpoint: &Endpoint, bucket: &str, key: &str) -> Result<TagSet, S3Error> {
+    let mut request = S3Request::new("GET", bucket, key);
+    request.subresources.push(("tagging".to_string(), String::new()));
+    let response = try!(s3request::send(endpoint, &request));
+
+    Ok(tags_from_xml(&response.body_str()))
+}
+
+fn tags_from_xml(xml: &str) -> TagSet {
+    xml_elements(xml, "Tag")
+        .into_iter()
+        .map(|tag| (xml_value(tag, "Key").unwrap_or(String::new()), xml_value(tag, "Value").unwrap_or(String::new())))
+        .collect()
+}
+
+/// website get|set|rm
+pub fn website<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let routing_rules = match matches.value_of("routing-rules") {
+                Some(path) => try!(read_config_file::<RoutingRules, P, D>(path, client)).rules,
+                None => Vec::new(),
+            };
+            let config = WebsiteConfiguration {
+                index_document: matches.value_of("index").unwrap_or("index.html").to_string(),
+                error_document: matches.value_of("error").map(|error| error.to_string()),
+                routing_rules: routing_rules,
+            };
+            let xml = try!(website_xml(&config).map_err(|e| config_error(e, client)));
+            put_config(bucket, "", "website", xml, client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, "", "website", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            match try!(get_config(bucket, "", "website", "NoSuchWebsiteConfiguration", client)) {
+                Some(body) => print_config(&website_from_xml(&body), client),
+                None => {
+                    if (client.output.format != OutputFormat::None) &&
+                       (client.output.format != OutputFormat::NoneAll) {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Website hosting is not enabled");
+                    }
+                },
+            }
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid website command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+fn website_xml(config: &WebsiteConfiguration) -> Result<String, String> {
+    if config.index_document.is_empty() || config.index_document.contains('/') {
+        return Err(format!("Invalid index document {} (must be a name such as index.html)", config.index_document));
+    }
+
+    let mut xml = String::from("<WebsiteConfiguration>");
+    xml.push_str(&format!("<IndexDocument><Suffix>{}</Suffix></IndexDocument>", xml_escape(&config.index_document)));
+    if let Some(ref error_document) = config.error_document {
+        xml.push_str(&format!("<ErrorDocument><Key>{}</Key></ErrorDocument>", xml_escape(error_document)));
+    }
+
+    if !config.routing_rules.is_empty() {
+        xml.push_str("<RoutingRules>");
+        for (index, rule) in config.routing_rules.iter().enumerate() {
+            let redirect = &rule.redirect;
+            if redirect.protocol.is_none() && redirect.host_name.is_none() &&
+               redirect.replace_key_prefix_with.is_none() && redirect.replace_key_with.is_none() &&
+               redirect.http_redirect_code.is_none() {
+                return Err(format!("Routing rule {} has an empty redirect", index + 1));
+            }
+            if redirect.replace_key_prefix_with.is_some() && redirect.replace_key_with.is_some() {
+                return Err(format!("Routing rule {} can not have both replace_key_prefix_with and replace_key_with",
+                                   index + 1));
+            }
+
+            xml.push_str("<RoutingRule>");
+            if let Some(ref condition) = rule.condition {
+                xml.push_str("<Condition>");
+                push_element(&mut xml, "KeyPrefixEquals", &condition.key_prefix_equals);
+                push_element(&mut xml, "HttpErrorCodeReturnedEquals", &condition.http_error_code_returned_equals);
+                xml.push_str("</Condition>");
+            }
+            xml.push_str("<Redirect>");
+            push_element(&mut xml, "Protocol", &redirect.protocol);
+            push_element(&mut xml, "HostName", &redirect.host_name);
+            push_element(&mut xml, "ReplaceKeyPrefixWith", &redirect.replace_key_prefix_with);
+            push_element(&mut xml, "ReplaceKeyWith", &redirect.replace_key_with);
+            push_element(&mut xml, "HttpRedirectCode", &redirect.http_redirect_code);
+            xml.push_str("</Redirect>");
+            xml.push_str("</RoutingRule>");
+        }
+        xml.push_str("</RoutingRules>");
+    }
+
+    xml.push_str("</WebsiteConfiguration>");
+    Ok(xml)
+}
+
+fn website_from_xml(xml: &str) -> WebsiteConfiguration {
+    let document = |tag: &str, field: &str| {
+        xml_elements(xml, tag).into_iter().next().and_then(|document| xml_value(document, field))
+    };
+
+    WebsiteConfiguration {
+        index_document: document("IndexDocument", "Suffix").unwrap_or(String::new()),
+        error_document: document("ErrorDocument", "Key"),
+        routing_rules: xml_elements(xml, "RoutingRule")
+            .into_iter()
+            .map(|rule| {
+                let redirect = xml_elements(rule, "Redirect").into_iter().next().unwrap_or("");
+                RoutingRule {
+                    condition: xml_elements(rule, "Condition").into_iter().next().map(|condition| {
+                        RoutingCondition {
+                            key_prefix_equals: xml_value(condition, "KeyPrefixEquals"),
+                            http_error_code_returned_equals: xml_value(condition, "HttpErrorCodeReturnedEquals"),
+                        }
+                    }),
+                    redirect: RoutingRedirect {
+                        protocol: xml_value(redirect, "Protocol"),
+                        host_name: xml_value(redirect, "HostName"),
+                        replace_key_prefix_with: xml_value(redirect, "ReplaceKeyPrefixWith"),
+                        replace_key_with: xml_value(redirect, "ReplaceKeyWith"),
+                        http_redirect_code: xml_value(redirect, "HttpRedirectCode"),
+                    },
+                }
+            })
+            .collect(),
+    }
+}
+
+// Appends <tag>value</tag> when there is a value.
+fn push_element(xml: &mut String, tag: &str, value: &Option<String>) {
+    if let Some(ref value) = *value {
+        xml.push_str(&format!("<{}>{}</{}>", tag, xml_escape(value), tag));
+    }
+}
+
+/// encryption get|set|rm
+pub fn encryption<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match matches.subcommand() {
+        ("set", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let key_id = matches.value_of("sse-kms-key-id");
+            let algorithm = match matches.value_of("algorithm").map(|algorithm| algorithm.to_lowercase()) {
+                Some(ref algorithm) if algorithm == "aes256" && key_id.is_none() => "AES256",
+                Some(ref algorithm) if algorithm == "aws:kms" => "aws:kms",
+                _ => {
+                    return Err(config_error("Encryption must be AES256 or aws:kms (--sse-kms-key-id needs aws:kms)"
+                                                .to_string(),
+                                            client))
+                },
+            };
+            let config = EncryptionConfiguration {
+                sse_algorithm: algorithm.to_string(),
+                kms_master_key_id: key_id.map(|key_id| key_id.to_string()),
+            };
+            put_config(bucket, "", "encryption", encryption_xml(&config), client)
+        },
+        ("rm", Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            delete_config(bucket, "", "encryption", client)
+        },
+        // Fall through to `get`
+        (_, Some(matches)) => {
+            let (bucket, _, _) = find_bucket_object_last(&matches);
+            let body = try!(get_config(bucket,
+                                       "",
+                                       "encryption",
+                                       "ServerSideEncryptionConfigurationNotFoundError",
+                                       client));
+            match body {
+                Some(body) => print_config(&encryption_from_xml(&body), client),
+                None => {
+                    if (client.output.format != OutputFormat::None) &&
+                       (client.output.format != OutputFormat::NoneAll) {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Default encryption is not set");
+                    }
+                },
+            }
+            Ok(())
+        },
+        (_, None) => {
+            let error = format!("Invalid encryption command");
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+fn encryption_xml(config: &EncryptionConfiguration) -> String {
+    let mut xml = String::from("<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault>");
+    xml.push_str(&format!("<SSEAlgorithm>{}</SSEAlgorithm>", config.sse_algorithm));
+    push_element(&mut xml, "KMSMasterKeyID", &config.kms_master_key_id);
+    xml.push_str("</ApplyServerSideEncryptionByDefault></Rule></ServerSideEncryptionConfiguration>");
+    xml
+}
+
+fn encryption_from_xml(xml: &str) -> EncryptionConfiguration {
+    EncryptionConfiguration {
+        sse_algorithm: xml_value(xml, "SSEAlgorithm").unwrap_or(String::new()),
+        kms_master_key_id: xml_value(xml, "KMSMasterKeyID"),
+    }
+}
+
+fn lifecycle_xml(config: &LifecycleConfiguration) -> Result<String, String> {
+    if config.rules.is_empty() {
+        return Err("At least one rule is required (use `lifecycle rm` to remove all rules)".to_string());
+    }
+
+    let mut xml = String::from("<LifecycleConfiguration>");
+
+    for (index, rule) in config.rules.iter().enumerate() {
+        if rule.expiration_days.is_none() && rule.noncurrent_version_expiration_days.is_none() &&
+           rule.abort_incomplete_multipart_upload_days.is_none() {
+            return Err(format!("Rule {} has no expiration, noncurrent version expiration or abort incomplete \
+                                multipart upload days",
+                               index + 1));
+        }
+
+        let status = match rule.status.as_ref().map(|status| status.to_lowercase()) {
+            None => "Enabled",
+            Some(ref status) if status == "enabled" => "Enabled",
+            Some(ref status) if status == "disabled" => "Disabled",
+            Some(status) => {
+                return Err(format!("Rule {} has status {} (must be Enabled or Disabled)", index + 1, status))
+            },
+        };
+
+        xml.push_str("<Rule>");
+        if let Some(ref id) = rule.id {
+            xml.push_str(&format!("<ID>{}</ID>", xml_escape(id)));
+        }
+        let prefix = rule.prefix.as_ref().map(|prefix| prefix.as_str()).unwrap_or("");
+        xml.push_str(&format!("<Prefix>{}</Prefix>", xml_escape(prefix)));
+        xml.push_str(&format!("<Status>{}</Status>", status));
+        if let Some(days) = rule.expiration_days {
+            xml.push_str(&format!("<Expiration><Days>{}</Days></Expiration>", days));
+        }
+        if let Some(days) = rule.noncurrent_version_expiration_days {
+            xml.push_str(&format!("<NoncurrentVersionExpiration><NoncurrentDays>{}</NoncurrentDays>\
+                                   </NoncurrentVersionExpiration>",
+                                  days));
+        }
+        if let Some(days) = rule.abort_incomplete_multipart_upload_days {
+            xml.push_str(&format!("<AbortIncompleteMultipartUpload><DaysAfterInitiation>{}</DaysAfterInitiation>\
+                                   </AbortIncompleteMultipartUpload>",
+                                  days));
+        }
+        xml.push_str("</Rule>");
+    }
+
+    xml.push_str("</LifecycleConfiguration>");
+    Ok(xml)
+}
+
+fn lifecycle_from_xml(xml: &str) -> LifecycleConfiguration {
+    let days = |rule: &str, tag: &str, field: &str| {
+        xml_elements(rule, tag).into_iter().next().and_then(|action| xml_value(action, field)).and_then(|days| {
+            days.parse::<u64>().ok()
+        })
+    };
+
+    LifecycleConfiguration {
+        rules: xml_elements(xml, "Rule")
+            .into_iter()
+            .map(|rule| {
+                LifecycleRule {
+                    id: xml_value(rule, "ID"),
+                    prefix: xml_value(rule, "Prefix"),
+                    status: xml_value(rule, "Status"),
+                    expiration_days: days(rule, "Expiration", "Days"),
+                    noncurrent_version_expiration_days: days(rule, "NoncurrentVersionExpiration", "NoncurrentDays"),
+                    abort_incomplete_multipart_upload_days: days(rule,
+                                                                 "AbortIncompleteMultipartUpload",
+                                                                 "DaysAfterInitiation"),
+                }
+            })
+            .collect(),
+    }
+}
+
+// Reads a configuration file. Files ending in .toml are TOML and anything else is JSON.
+fn read_config_file<T, P, D>(path: &str, client: &Client<P, D>) -> Result<T, S3Error>
+    where T: Decodable,
+          P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let contents = try!(read_file(path, client));
+    let is_toml = Path::new(path).extension().map(|extension| extension == "toml").unwrap_or(false);
+    let config = if is_toml {
+        toml::decode_str::<T>(&contents).ok_or(format!("{} is not a valid TOML configuration", path))
+    } else {
+        json::decode::<T>(&contents).map_err(|e| format!("{} is not a valid JSON configuration: {}", path, e))
+    };
+
+    config.map_err(|e| config_error(e, client))
+}
+
+fn read_file<P, D>(path: &str, client: &Client<P, D>) -> Result<String, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if path.is_empty() {
+        return Err(config_error("Configuration file was not specified".to_string(), client));
+    }
+
+    let mut contents = String::new();
+    match File::open(path).and_then(|mut file| file.read_to_string(&mut contents)) {
+        Ok(_) => Ok(contents),
+        Err(e) => Err(config_error(format!("Error reading {}: {}", path, e), client)),
+    }
+}
+
+fn config_error<P, D>(error: String, client: &Client<P, D>) -> S3Error
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+    S3Error::new(error)
+}
+
+// Returns the sub-resource document or None if the bucket (or object when key is not empty) does
+// not have one (missing_code).
+fn get_config<P, D>(bucket: &str, key: &str, subresource: &str, missing_code: &str, client: &Client<P, D>)
+                    -> Result<Option<String>, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        return Err(config_error("Bucket was not specified".to_string(), client));
+    }
+
+    let mut request = S3Request::new("GET", bucket, key);
+    request.subresources.push((subresource.to_string(), String::new()));
+
+    match s3request::send(client.s3client.endpoint(), &request) {
+        Ok(response) => Ok(Some(response.body_str())),
+        Err(ref e) if e.aws.code == missing_code => Ok(None),
+        Err(e) => Err(config_error(format!("{:#?}", e), client)),
+    }
+}
+
+fn put_config<P, D>(bucket: &str, key: &str, subresource: &str, body: String, client: &Client<P, D>)
+                    -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        return Err(config_error("Bucket was not specified".to_string(), client));
+    }
+
+    let payload = body.into_bytes();
+    let mut request = S3Request::new("PUT", bucket, key);
+    request.subresources.push((subresource.to_string(), String::new()));
+    // Content-MD5 is required for most configuration PUTs so it is always sent.
+    request.headers.push(("Content-MD5".to_string(), md5::compute(&payload).to_base64(STANDARD)));
+    request.payload = Some(payload);
+
+    match s3request::send(client.s3client.endpoint(), &request) {
+        Ok(_) => {
+            print_success(client);
+            Ok(())
+        },
+        Err(e) => Err(config_error(format!("{:#?}", e), client)),
+    }
+}
+
+fn delete_config<P, D>(bucket: &str, key: &str, subresource: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        return Err(config_error("Bucket was not specified".to_string(), client));
+    }
+
+    let mut request = S3Request::new("DELETE", bucket, key);
+    request.subresources.push((subresource.to_string(), String::new()));
+
+    match s3request::send(client.s3client.endpoint(), &request) {
+        Ok(_) => {
+            print_success(client);
+            Ok(())
+        },
+        Err(e) => Err(config_error(format!("{:#?}", e), client)),
+    }
+}
+
+fn print_success<P, D>(client: &Client<P, D>)
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet, client.output.color, "Success");
+    }
+}
+
+fn print_config<T, P, D>(config: &T, client: &Client<P, D>)
+    where T: Encodable + fmt::Debug,
+          P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match client.output.format {
+        OutputFormat::Serialize => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", config);
+        },
+        OutputFormat::Plain => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", config);
+        },
+        OutputFormat::JSON => {
+            println_color_quiet!(client.is_quiet,
+                                 client.output.color,
+                                 "{}",
+                                 json::encode(config).unwrap_or("{}".to_string()));
+        },
+        OutputFormat::PrettyJSON => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(config));
+        },
+        OutputFormat::Simple => {
+            println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", config);
+        },
+        _ => {},
+    }
+}
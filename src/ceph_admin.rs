@@ -44,6 +44,8 @@ use aws_sdk_rust::aws::s3::admin::*;
 // Use this for signing the admin feature for Ceph RGW
 use aws_sdk_rust::aws::common::signature::*;
 
+use common::confirm_action;
+
 use Client;
 use Output;
 use OutputFormat;
@@ -146,6 +148,7 @@ fn buckets<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D>) -> R
                 println_color_quiet!(client.is_quiet, client.error.color, "{:#?}", e);
                 return Err(e);
             }
+            try!(confirm_action(&format!("delete bucket {} and purge all of its objects", bucket), client));
             path += "bucket";
             method = "DELETE".to_string();
 
@@ -672,6 +675,10 @@ fn user<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D>) -> Resu
         },
     }
 
+    if method == "DELETE" && params.get("purge-data").map(|purge| purge == "true").unwrap_or(false) {
+        try!(confirm_action(&format!("delete user {} and purge all of their data", params["uid"]), client));
+    }
+
     let mut request = AdminRequest::default();
     request.bucket = Some(bucket.to_string());
     request.method = Some(method);
@@ -951,6 +958,11 @@ fn usage<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D>) -> Res
         },
     }
 
+    if method == "DELETE" {
+        let user = params.get("uid").map(|user| format!("user {}", user)).unwrap_or("all users".to_string());
+        try!(confirm_action(&format!("trim the usage log of {}", user), client));
+    }
+
     let mut request = AdminRequest::default();
     request.bucket = Some(bucket.to_string());
     request.method = Some(method);
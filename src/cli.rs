@@ -56,6 +56,13 @@ pub fn build_cli<'a>(app: &str, home: &'a str, version: &'a str) -> App<'a, 'a>
      .short("h")
      .long("bucket-virtual-host")
      .help("Overrides the default of TRUE for virtual buckets. Useful for non AWS environments"))
+   .arg(Arg::with_name("threads")
+      .short("j")
+      .long("threads")
+      .value_name("N")
+      .default_value("4")
+      .help("Number of concurrent connections used for multipart uploads and parallel downloads")
+      .takes_value(true))
    .arg(Arg::with_name("keep-alive")
      .short("k")
      .long("keep-alive")
@@ -127,9 +134,10 @@ pub fn build_cli<'a>(app: &str, home: &'a str, version: &'a str) -> App<'a, 'a>
          .about("Get Bucket ACLs: s3lsio acl get s3://<bucket>")
          .arg_from_usage("[bucket] 'Bucket name'"))
       .subcommand(SubCommand::with_name("set")
-         .about("Set Bucket ACLs: s3lsio acl set <acls> s3://<bucket>")
-         .arg_from_usage("[acls] 'ACLs - public-read, public-readwrite, private'")
-         .arg_from_usage("[bucket] 'Bucket name'")))
+         .about("Set Bucket or Object ACLs: s3lsio acl set <acls> s3://<bucket>[/<object>] and/or --grant-* options")
+         .arg_from_usage("[acls] 'Canned ACL - private, public-read, public-read-write, authenticated-read, bucket-owner-full-control etc.'")
+         .arg_from_usage("[bucket] 'Bucket name or Bucket/Object name'")
+         .args(&grant_args())))
    .subcommand(SubCommand::with_name("bench")
       .about("Benchmarking: s3lsio bench <command> <options>")
       .subcommand(SubCommand::with_name("gen")
@@ -152,42 +160,140 @@ pub fn build_cli<'a>(app: &str, home: &'a str, version: &'a str) -> App<'a, 'a>
          .arg_from_usage("[bucket] 'Bucket name/object name'")))
    .subcommand(SubCommand::with_name("head")
       .about("Head Bucket or Object: s3lsio head s3://<bucket> or s3lsio head s3://<bucket>/<object>")
-      .arg_from_usage("[bucket] 'Bucket name'"))
+      .arg_from_usage("[bucket] 'Bucket name'")
+      .arg_from_usage("--sse-c-key-file [sse_c_key_file] 'File with the 32 byte SSE-C key of the object'"))
    .subcommand(SubCommand::with_name("ls")
       .about("List Buckets or Objects in bucket with optional version tag: s3lsio ls OR s3lsio ls s3://<bucket>/<prefix> ver or multi")
       .arg_from_usage("[bucket] 'Bucket name'")
       .arg_from_usage("[option] 'ver or multi'")
-      .arg_from_usage("[upload_id] 'multipart upload ID option'"))
+      .arg_from_usage("[upload_id] 'multipart upload ID option'")
+      .arg_from_usage("--recursive 'List every key under the prefix instead of grouping them by /'")
+      .arg_from_usage("--max-keys [max_keys] 'Stop after this many keys (all keys are listed by default)'")
+      .arg_from_usage("--list-version [list_version] 'ListObjects version 1 (markers, default) or 2 (continuation tokens)'")
+      .args(&filter_args()))
    .subcommand(SubCommand::with_name("mb")
       .about("Make Bucket: s3lsio mb s3://<bucket>")
       .arg_from_usage("[bucket] 'Bucket name'"))
    .subcommand(SubCommand::with_name("rb")
-      .about("Remove Bucket: s3lsio rb s3://<bucket>")
-      .arg_from_usage("[bucket] 'Bucket name'"))
+      .about("Remove Bucket: s3lsio rb s3://<bucket> OR s3lsio rb --force s3://<bucket> (removes everything in it first)")
+      .arg_from_usage("[bucket] 'Bucket name'")
+      .arg_from_usage("--force 'Delete all objects, versions, delete markers and multipart uploads first (asks unless --yes)'"))
    .subcommand(SubCommand::with_name("rm")
-      .about("Remove Object and/or Object version: s3lsio rm s3://<bucket>/<object> <version>")
+      .about("Remove Object and/or Object version: s3lsio rm s3://<bucket>/<object> <version> OR s3lsio rm --recursive s3://<bucket>/<prefix>/")
       .arg_from_usage("[bucket] 'Bucket name'")
-      .arg_from_usage("[version] 'Version'"))
+      .arg_from_usage("[version] 'Version'")
+      .arg_from_usage("--recursive 'Remove every object under the prefix in batches of up to 1000 keys'")
+      .arg_from_usage("--dry-run 'Show what would be removed without removing anything'")
+      .args(&filter_args()))
+   .subcommand(SubCommand::with_name("encryption")
+      .about("Shows Bucket default encryption: s3lsio encryption get s3://<bucket>")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Sets Bucket default encryption: s3lsio encryption set AES256|aws:kms s3://<bucket>")
+         .arg_from_usage("[algorithm] 'AES256 or aws:kms'")
+         .arg_from_usage("[bucket] 'Bucket name'")
+         .arg_from_usage("--sse-kms-key-id [sse_kms_key_id] 'KMS key ID (default is the account aws/s3 key)'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Removes Bucket default encryption: s3lsio encryption rm s3://<bucket>")
+         .arg_from_usage("[bucket] 'Bucket name'")))
    .subcommand(SubCommand::with_name("get")
       .about("Get Object (use `cp`): s3lsio get s3://<bucket>/<object> <path>")
       .arg_from_usage("[bucket] 'Bucket name'")
-      .arg_from_usage("[path] 'Path'"))
+      .arg_from_usage("[path] 'Path'")
+      .arg_from_usage("--parallel 'Download byte ranges of the object in parallel (see --threads)'")
+      .arg_from_usage("--resume 'Continue an interrupted download from the partial local file'")
+      .arg_from_usage("--sse-c-key-file [sse_c_key_file] 'File with the 32 byte SSE-C key of the object'"))
+   .subcommand(SubCommand::with_name("cors")
+      .about("Shows Bucket CORS rules: s3lsio cors get s3://<bucket>")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Sets Bucket CORS rules from a JSON file: s3lsio cors set <file> s3://<bucket>")
+         .arg_from_usage("[file] 'Rule file'")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Removes all Bucket CORS rules: s3lsio cors rm s3://<bucket>")
+         .arg_from_usage("[bucket] 'Bucket name'")))
    .subcommand(SubCommand::with_name("cp")
-      .about("Copy Object: s3lsio cp s3://<bucket>/<object> <path> OR s3lsio cp <path> s3://<bucket>/<object> <size of parts>")
+      .about("Copy Object: s3lsio cp s3://<bucket>/<object> <path> OR s3lsio cp <path> s3://<bucket>/<object> <size of parts> OR s3lsio cp s3://<bucket>/<object> s3://<bucket>/<object> OR s3lsio cp --recursive <dir> s3://<bucket>/<prefix>/")
       .arg_from_usage("[bucket] 'Bucket name/object name'")
       .arg_from_usage("[path] 'Path'")
-      .arg_from_usage("[size] 'Size of parts'"))
+      .arg_from_usage("[size] 'Size of parts'")
+      .arg_from_usage("--recursive 'Copy every file under a directory or every object under a prefix'")
+      .arg_from_usage("--parallel 'Download byte ranges of the object in parallel (see --threads)'")
+      .arg_from_usage("--resume 'Continue an interrupted download from the partial local file'")
+      .args(&filter_args())
+      .args(&upload_args()))
+   .subcommand(SubCommand::with_name("mv")
+      .about("Move Object (source is removed once the copy is verified): s3lsio mv s3://<bucket>/<object> <path> OR s3lsio mv <path> s3://<bucket>/<object> OR s3lsio mv s3://<bucket>/<object> s3://<bucket>/<object>")
+      .arg_from_usage("[bucket] 'Bucket name/object name'")
+      .arg_from_usage("[path] 'Path'")
+      .arg_from_usage("[size] 'Size of parts'")
+      .arg_from_usage("--recursive 'Move every file under a directory or every object under a prefix'")
+      .arg_from_usage("--parallel 'Download byte ranges of the object in parallel (see --threads)'")
+      .arg_from_usage("--resume 'Continue an interrupted download from the partial local file'")
+      .args(&filter_args())
+      .args(&upload_args()))
+   .subcommand(SubCommand::with_name("presign")
+      .about("Presigned URL for an Object: s3lsio presign s3://<bucket>/<object> --expires <seconds> --method GET|PUT")
+      .arg_from_usage("[bucket] 'Bucket name/object name'")
+      .arg_from_usage("--expires [expires] 'Seconds the URL is valid for (default 3600, at most 604800 with V4)'")
+      .arg_from_usage("--method [method] 'HTTP method the URL is signed for: GET (default), PUT, HEAD or DELETE'"))
    .subcommand(SubCommand::with_name("put")
       .about("Put Object (use `cp`) <size of parts> is optional: s3lsio put <path> s3://<bucket>/<object> <size of parts>")
       .arg_from_usage("[path] 'Path of where to find object'")
       .arg_from_usage("[bucket] 'Bucket name'")
-      .arg_from_usage("[size] 'Size of parts for multipart upload'"))
+      .arg_from_usage("[size] 'Size of parts for multipart upload'")
+      .args(&upload_args()))
    .subcommand(SubCommand::with_name("range")
       .about("Byte-Range request of Object: s3lsio range <offset> <len> s3://<bucket>/<object> <path>")
       .arg_from_usage("[offset] 'Range begin offset'")
       .arg_from_usage("[len] 'Range len'")
       .arg_from_usage("[bucket] 'Bucket name/object name'")
-      .arg_from_usage("[path] 'Path'"))
+      .arg_from_usage("[path] 'Path'")
+      .arg_from_usage("--sse-c-key-file [sse_c_key_file] 'File with the 32 byte SSE-C key of the object'"))
+   .subcommand(SubCommand::with_name("sync")
+      .about("Sync directory and/or prefix: s3lsio sync <dir> s3://<bucket>/<prefix>/ OR s3lsio sync s3://<bucket>/<prefix>/ <dir> OR s3lsio sync s3://<bucket>/<prefix>/ s3://<bucket>/<prefix>/")
+      .arg_from_usage("[src] 'Source directory or s3://<bucket>/<prefix>'")
+      .arg_from_usage("[dst] 'Destination directory or s3://<bucket>/<prefix>'")
+      .arg_from_usage("--delete 'Remove anything in the destination that is not in the source'")
+      .arg_from_usage("--dry-run 'Show what would be transferred or deleted without doing it'")
+      .arg_from_usage("--checksum 'Compare MD5 against the ETag instead of modified time when sizes match'")
+      .args(&filter_args()))
+   .subcommand(SubCommand::with_name("lifecycle")
+      .about("Shows Bucket Lifecycle rules: s3lsio lifecycle get s3://<bucket>")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Sets Bucket Lifecycle rules from a JSON or TOML file: s3lsio lifecycle set <file> s3://<bucket>")
+         .arg_from_usage("[file] 'Rule file'")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Removes all Bucket Lifecycle rules: s3lsio lifecycle rm s3://<bucket>")
+         .arg_from_usage("[bucket] 'Bucket name'")))
+   .subcommand(SubCommand::with_name("policy")
+      .about("Shows Bucket Policy: s3lsio policy get s3://<bucket>")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Sets Bucket Policy from a JSON file: s3lsio policy set <file> s3://<bucket>")
+         .arg_from_usage("[file] 'Policy file'")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Removes Bucket Policy: s3lsio policy rm s3://<bucket>")
+         .arg_from_usage("[bucket] 'Bucket name'")))
+   .subcommand(SubCommand::with_name("tag")
+      .about("Shows Bucket or Object tags: s3lsio tag get s3://<bucket>[/<object>]")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name or Bucket/Object name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Replaces Bucket or Object tags: s3lsio tag set s3://<bucket>[/<object>] <key>=<value>...")
+         .arg_from_usage("[bucket] 'Bucket name or Bucket/Object name'")
+         .arg_from_usage("[tags]... 'Tags as key=value'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Removes all Bucket or Object tags: s3lsio tag rm s3://<bucket>[/<object>]")
+         .arg_from_usage("[bucket] 'Bucket name or Bucket/Object name'")))
    .subcommand(SubCommand::with_name("ver")
       .about("Shows Bucket Versioning: s3lsio ver get s3://<bucket>")
       .subcommand(SubCommand::with_name("get")
@@ -196,6 +302,19 @@ pub fn build_cli<'a>(app: &str, home: &'a str, version: &'a str) -> App<'a, 'a>
          .about("Enables Bucket Versioning: s3lsio ver set on|off s3://<bucket>")
          .arg_from_usage("[ver] 'On or Off'")
          .arg_from_usage("[bucket] 'Bucket name'")))
+   .subcommand(SubCommand::with_name("website")
+      .about("Shows Bucket Website hosting: s3lsio website get s3://<bucket>")
+      .subcommand(SubCommand::with_name("get")
+         .arg_from_usage("[bucket] 'Bucket name'"))
+      .subcommand(SubCommand::with_name("set")
+         .about("Enables Bucket Website hosting: s3lsio website set s3://<bucket> --index index.html --error 404.html")
+         .arg_from_usage("[bucket] 'Bucket name'")
+         .arg_from_usage("--index [index] 'Index document suffix (default index.html)'")
+         .arg_from_usage("--error [error] 'Key of the error document'")
+         .arg_from_usage("--routing-rules [routing_rules] 'JSON or TOML file of routing rules'"))
+      .subcommand(SubCommand::with_name("rm")
+         .about("Disables Bucket Website hosting: s3lsio website rm s3://<bucket>")
+         .arg_from_usage("[bucket] 'Bucket name'")))
     // Ceph RGW Admin Section...
    .subcommand(SubCommand::with_name("admin")
        .about("Admin Options: s3lsio admin <command> <options>")
@@ -284,3 +403,110 @@ pub fn build_cli<'a>(app: &str, home: &'a str, version: &'a str) -> App<'a, 'a>
              .arg_from_usage("[access_key] '(Optional) Access Key ID (default to true)'")
              .arg_from_usage("[secret_key] '(Optional) Secret Key ID (default to true)'"))))
 }
+
+// --include/--exclude/--regex shared by the commands that work on a listing of keys. Each pattern
+// takes exactly one value so repeating the option does not swallow the positional arguments.
+fn filter_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+  vec![Arg::with_name("include")
+         .long("include")
+         .value_name("PATTERN")
+         .help("Only keys (relative to the prefix) matching the glob pattern. Can be repeated")
+         .takes_value(true)
+         .multiple(true)
+         .number_of_values(1),
+       Arg::with_name("exclude")
+         .long("exclude")
+         .value_name("PATTERN")
+         .help("Skip keys (relative to the prefix) matching the glob pattern. Can be repeated")
+         .takes_value(true)
+         .multiple(true)
+         .number_of_values(1),
+       Arg::with_name("regex")
+         .long("regex")
+         .help("Treat the --include and --exclude patterns as regular expressions instead of globs")]
+}
+
+// --grant-* options for `acl set` and uploads.
+fn grant_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+  vec![("grant-read", "Grantee (id=<canonical id>, email=<address> or uri=<group uri>) can read. Can be repeated"),
+       ("grant-write", "Grantee (id=, email= or uri=) can write objects in the bucket. Can be repeated"),
+       ("grant-read-acp", "Grantee (id=, email= or uri=) can read the ACL. Can be repeated"),
+       ("grant-write-acp", "Grantee (id=, email= or uri=) can write the ACL. Can be repeated"),
+       ("grant-full-control", "Grantee (id=, email= or uri=) has full control. Can be repeated")]
+    .into_iter()
+    .map(|(name, help)| {
+      Arg::with_name(name)
+        .long(name)
+        .value_name("GRANTEE")
+        .help(help)
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+    })
+    .collect()
+}
+
+// Options that apply to the object created by put, cp or mv.
+fn upload_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+  let mut args = vec![Arg::with_name("acl")
+                        .long("acl")
+                        .value_name("ACL")
+                        .help("Canned ACL for the new object (see `acl set`)")
+                        .takes_value(true),
+                      Arg::with_name("tag")
+                        .long("tag")
+                        .value_name("KEY=VALUE")
+                        .help("Tag for the new object. Can be repeated")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1),
+                      Arg::with_name("sse")
+                        .long("sse")
+                        .value_name("ALGORITHM")
+                        .help("Server-side encryption: AES256 or aws:kms")
+                        .takes_value(true),
+                      Arg::with_name("sse-kms-key-id")
+                        .long("sse-kms-key-id")
+                        .value_name("KEY_ID")
+                        .help("KMS key ID for --sse aws:kms (implies aws:kms)")
+                        .takes_value(true),
+                      Arg::with_name("sse-c-key-file")
+                        .long("sse-c-key-file")
+                        .value_name("FILE")
+                        .help("File holding a 32 byte key for SSE-C (also used to read the object on a download)")
+                        .takes_value(true),
+                      Arg::with_name("content-type")
+                        .long("content-type")
+                        .value_name("TYPE")
+                        .help("Content-Type of the new object. Guessed from the file extension by default")
+                        .takes_value(true),
+                      Arg::with_name("cache-control")
+                        .long("cache-control")
+                        .value_name("VALUE")
+                        .help("Cache-Control of the new object")
+                        .takes_value(true),
+                      Arg::with_name("content-disposition")
+                        .long("content-disposition")
+                        .value_name("VALUE")
+                        .help("Content-Disposition of the new object")
+                        .takes_value(true),
+                      Arg::with_name("content-encoding")
+                        .long("content-encoding")
+                        .value_name("VALUE")
+                        .help("Content-Encoding of the new object")
+                        .takes_value(true),
+                      Arg::with_name("storage-class")
+                        .long("storage-class")
+                        .value_name("CLASS")
+                        .help("Storage class: STANDARD, STANDARD_IA, REDUCED_REDUNDANCY, GLACIER etc.")
+                        .takes_value(true),
+                      Arg::with_name("metadata")
+                        .long("metadata")
+                        .value_name("KEY=VALUE")
+                        .help("User metadata (sent as x-amz-meta-KEY) for the new object. Can be repeated")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)];
+  args.extend(grant_args());
+  args
+}
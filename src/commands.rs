@@ -20,30 +20,51 @@
 use std::io;
 use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
-use std::fs::File;
+use std::fs;
+use std::fs::{File, OpenOptions};
 use std::ffi::OsStr;
+use std::cmp;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
 use md5;
 
 use term;
+use rustc_serialize::{Decodable, Encodable};
 use rustc_serialize::json;
 use rustc_serialize::base64::{STANDARD, ToBase64};
+use chrono::UTC;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 
 use clap::ArgMatches;
 use aws_sdk_rust::aws::errors::s3::S3Error;
-use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::s3::s3client::S3Client;
+use aws_sdk_rust::aws::s3::endpoint::Endpoint;
+use aws_sdk_rust::aws::common::credentials::{AwsCredentialsProvider, DefaultCredentialsProviderSync};
 use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
-use aws_sdk_rust::aws::common::common::Operation;
+use aws_sdk_rust::aws::common::common::{Operation, Owner};
 use aws_sdk_rust::aws::common::params::*;
 use aws_sdk_rust::aws::s3::acl::*;
 use aws_sdk_rust::aws::s3::bucket::*;
 use aws_sdk_rust::aws::s3::object::*;
 use aws_sdk_rust::aws::s3::admin::*;
+use aws_sdk_rust::aws::s3::writeparse::CommonPrefix;
 
 // Use this for signing the admin feature for Ceph RGW
 use aws_sdk_rust::aws::common::signature::*;
 
 use common::*;
 use ceph_admin::admin;
+use s3request;
+use s3request::{ObjectPage, S3Request, S3Response};
+use sync::sync;
+use bucket_config::{cors, encryption, lifecycle, object_tags, policy, tag, website};
+use filter::{key_filter, KeyFilter};
+use headers::{acl_headers, copy_source_headers, guess_content_type, is_metadata_header, sse_customer_headers,
+              tagging_header, upload_headers, CANNED_ACLS};
+use presign::presign;
 
 use Client;
 use Output;
@@ -52,6 +73,107 @@ use Commands;
 
 // 5MB minimum size for multipart_uploads. Only last part can be less.
 const PART_SIZE_MIN: u64 = 5242880;
+// S3 allows at most 10,000 parts in a multipart upload.
+const PART_COUNT_MAX: u64 = 10000;
+// 5GB is the largest object a single PUT will accept.
+const PUT_SIZE_MAX: u64 = 5368709120;
+// 8MB ranges are requested at a time when streaming an object to disk.
+const GET_CHUNK_SIZE: u64 = 8388608;
+// DeleteObjects accepts at most 1000 keys per request.
+const DELETE_BATCH_MAX: usize = 1000;
+// 512MB parts are used when copying objects over 5GB (server side so larger parts are cheap).
+const COPY_PART_SIZE: u64 = 536870912;
+
+/// Local record of a multipart upload in progress so an interrupted put can be resumed.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+struct UploadState {
+    bucket: String,
+    key: String,
+    upload_id: String,
+    size: u64,
+    modified: u64,
+    part_size: u64,
+    parts: Vec<UploadStatePart>,
+}
+
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+struct UploadStatePart {
+    part_number: u64,
+    e_tag: String,
+}
+
+/// Local record of the object a partial download belongs to so it is only resumed if unchanged.
+///
+#[derive(Debug, Default, Clone, RustcDecodable, RustcEncodable)]
+struct DownloadState {
+    bucket: String,
+    key: String,
+    e_tag: String,
+    last_modified: String,
+}
+
+/// One page of ls output. The fields are those of the SDK's ListObjectsOutput but sizes and
+/// counts are u64 since the SDK's i32 wraps for objects of 2GB or more.
+///
+#[derive(Debug, Default, RustcEncodable)]
+struct ListObjectsPage {
+    name: String,
+    next_marker: String,
+    delimiter: String,
+    max_keys: u64,
+    prefix: String,
+    marker: String,
+    encoding_type: String,
+    is_truncated: bool,
+    contents: Vec<ListObjectsEntry>,
+    common_prefixes: Vec<CommonPrefix>,
+    continuation_token: String,
+    next_continuation_token: String,
+    key_count: u64,
+    start_after: String,
+}
+
+#[derive(Debug, Default, RustcEncodable)]
+struct ListObjectsEntry {
+    last_modified: String,
+    e_tag: String,
+    storage_class: String,
+    key: String,
+    owner: Owner,
+    size: u64,
+}
+
+/// HEAD Object output. The fields are those of the SDK's HeadObjectOutput but content_length is
+/// u64 since the SDK's i32 can not hold the size of objects of 2GB or more.
+///
+#[derive(Debug, Default, RustcEncodable)]
+struct ObjectHead {
+    last_modified: String,
+    request_charged: String,
+    content_encoding: String,
+    replication_status: String,
+    storage_class: String,
+    server_side_encryption: String,
+    ssekms_key_id: String,
+    content_disposition: String,
+    metadata: HashMap<String, String>,
+    accept_ranges: String,
+    website_redirect_location: String,
+    expires: String,
+    delete_marker: bool,
+    cache_control: String,
+    content_length: u64,
+    expiration: String,
+    missing_meta: i32,
+    restore: String,
+    sse_customer_algorithm: String,
+    content_type: String,
+    content_language: String,
+    version_id: String,
+    e_tag: String,
+    sse_customer_key_md5: String,
+}
 
 /// Commands
 pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P, D>) -> Result<(), S3Error>
@@ -60,6 +182,10 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
 {
     let (mut bucket, mut object, last) = find_bucket_object_last(&matches);
 
+    // Only get, range, head, put, cp and mv have --sse-c-key-file.
+    let sse_customer_headers = try!(sse_customer_headers(matches, client));
+    client.sse_customer_headers = sse_customer_headers;
+
     match cmd {
         Commands::get => {
             let mut path = matches.value_of("path").unwrap_or("").to_string();
@@ -76,20 +202,30 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
                                last);
             }
 
-            cmd_get(bucket, &object, &path, client);
+            cmd_get(bucket, &object, &path, matches.is_present("parallel"), matches.is_present("resume"), client);
             Ok(())
         },
         Commands::put => {
+            let headers = try!(upload_headers(matches, client));
+            client.upload_headers = headers;
             let path = matches.value_of("path").unwrap_or("");
             let part_size: u64 = matches.value_of("size").unwrap_or("0").parse().unwrap_or(0);
             cmd_put(bucket, &object, path, part_size, client);
             Ok(())
         },
-        Commands::cp => {
+        // mv is a cp that removes the source once the copy has been verified.
+        Commands::cp | Commands::mv => {
+            let headers = try!(upload_headers(matches, client));
+            client.upload_headers = headers;
+            let is_move = cmd == Commands::mv;
             let mut get: bool = true;
             let mut path = matches.value_of("path").unwrap_or("").to_string();
+            let is_copy = path.contains("s3://") && matches.value_of("bucket").unwrap_or("").contains("s3://");
+            let (src_bucket, src_object) = (bucket, object.clone());
             if path.contains("s3://") {
                 get = false;
+                // The bucket arg holds the local path in this case so start the object over.
+                object = String::new();
                 let (scheme, tmp_bucket) = matches.value_of("path").unwrap_or("s3:// ").split_at(5);
 
                 if tmp_bucket.contains('/') {
@@ -117,14 +253,34 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
                 path = matches.value_of("bucket").unwrap_or("").to_string();
             }
 
-            if get {
-                cmd_get(bucket, &object, &path, client);
+            let is_recursive = matches.is_present("recursive");
+            let filter = try!(key_filter(matches, client));
+            let is_parallel = matches.is_present("parallel");
+            let is_resume = matches.is_present("resume");
+            if is_copy && is_recursive {
+                cmd_copy_recursive(src_bucket, &src_object, bucket, &object, &filter, is_move, client)
+            } else if is_copy && is_move {
+                move_copy(src_bucket, &src_object, bucket, &object, client)
+            } else if is_copy {
+                copy_object(src_bucket, &src_object, bucket, &object, client)
+            } else if get && is_recursive {
+                cmd_get_recursive(bucket, &object, &path, is_parallel, is_resume, &filter, is_move, client)
+            } else if get && is_move {
+                move_get(bucket, &object, &path, is_parallel, is_resume, client)
+            } else if get {
+                cmd_get(bucket, &object, &path, is_parallel, is_resume, client);
+                Ok(())
             } else {
                 let part_size: u64 = matches.value_of("size").unwrap_or("0").parse().unwrap_or(0);
-                cmd_put(bucket, &object, &path, part_size, client);
+                if is_recursive {
+                    cmd_put_recursive(bucket, &object, &path, part_size, &filter, is_move, client)
+                } else if is_move {
+                    move_put(bucket, &object, &path, part_size, client)
+                } else {
+                    cmd_put(bucket, &object, &path, part_size, client);
+                    Ok(())
+                }
             }
-
-            Ok(())
         },
         Commands::range => {
             let offset: u64 = matches.value_of("offset").unwrap_or("0").parse().unwrap_or(0);
@@ -144,6 +300,38 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
         },
         Commands::rm => {
             let version = matches.value_of("version").unwrap_or("");
+            let filter = try!(key_filter(matches, client));
+            let is_dry_run = matches.is_present("dry-run");
+            if matches.is_present("recursive") {
+                return delete_objects_recursive(bucket, &object, &filter, is_dry_run, client);
+            }
+            // Same as a listing of the object's parent prefix: the pattern sees the key relative to it.
+            let relative = object.rfind('/').map(|index| &object[index + 1..]).unwrap_or(&object);
+            if !filter.is_match(relative) {
+                println_color_quiet!(client.is_quiet,
+                                     client.output.color,
+                                     "Skipping s3://{}/{} (filtered)",
+                                     bucket,
+                                     object);
+                return Ok(());
+            }
+            if is_dry_run {
+                println_color_quiet!(client.is_quiet,
+                                     client.output.color,
+                                     "(dry-run) delete: s3://{}/{}",
+                                     bucket,
+                                     object);
+                return Ok(());
+            }
+            try!(confirm_action(&format!("delete s3://{}/{}{}",
+                                         bucket,
+                                         object,
+                                         if version.is_empty() {
+                                             String::new()
+                                         } else {
+                                             format!(" (version {})", version)
+                                         }),
+                                client));
             let mut operation = Operation::default();
             let result = delete_object(bucket, &object, version, Some(&mut operation), client);
             //println!("{:#?}", operation);
@@ -151,6 +339,7 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
         },
         Commands::abort => {
             let upload_id = matches.value_of("upload_id").unwrap_or("");
+            try!(confirm_action(&format!("abort upload {} of s3://{}/{}", upload_id, bucket, object), client));
             let result = abort_multipart_upload(bucket, &object, upload_id, client);
             Ok(())
         },
@@ -168,6 +357,10 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
         Commands::ls => {
             // NB: object is prefix for ls cmd
             let option = matches.value_of("option").unwrap_or("");
+            let list_version: u16 = matches.value_of("list-version").unwrap_or("1").parse().unwrap_or(1);
+            let max_keys: Option<u64> = matches.value_of("max-keys").and_then(|max_keys| max_keys.parse().ok());
+            let delimiter = if matches.is_present("recursive") { "" } else { "/" };
+            let filter = try!(key_filter(matches, client));
             if bucket.is_empty() {
                 let list = try!(get_buckets_list(client));
             } else if bucket.contains('/') {
@@ -176,7 +369,8 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
                 if components[1].is_empty() {
                     // List objects in bucket.
                     if option.is_empty() {
-                        let list = try!(get_object_list(bucket, &object, 1, client));
+                        let list =
+                            try!(get_object_list(bucket, &object, delimiter, list_version, max_keys, &filter, client));
                     } else if option == "multi" {
                         let upload_id = matches.value_of("upload_id").unwrap_or("");
                         let list = try!(get_object_multipart_list(bucket, upload_id, &object, client));
@@ -185,7 +379,8 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
                     }
                 }
             } else if option.is_empty() {
-                    let list = try!(get_object_list(bucket, &object, 1, client));
+                    let list =
+                        try!(get_object_list(bucket, &object, delimiter, list_version, max_keys, &filter, client));
                 } else if option == "multi" {
                     let upload_id = matches.value_of("upload_id").unwrap_or("");
                     let list = try!(get_object_multipart_list(bucket, upload_id, &object, client));
@@ -206,11 +401,40 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
             }
         },
         Commands::rb => {
-            delete_bucket(bucket, client)
+            if matches.is_present("force") {
+                force_delete_bucket(bucket, client)
+            } else {
+                try!(confirm_action(&format!("delete bucket {}", bucket), client));
+                delete_bucket(bucket, client)
+            }
+        },
+        Commands::cors => {
+            cors(matches, client)
+        },
+        Commands::encryption => {
+            encryption(matches, client)
+        },
+        Commands::lifecycle => {
+            lifecycle(matches, client)
+        },
+        Commands::policy => {
+            policy(matches, client)
+        },
+        Commands::presign => {
+            presign(matches, client)
+        },
+        Commands::tag => {
+            tag(matches, client)
         },
         Commands::ver => {
             ver(matches, client)
         },
+        Commands::website => {
+            website(matches, client)
+        },
+        Commands::sync => {
+            sync(matches, client)
+        },
         // Ceph RGW Admin Section...
         Commands::admin => {
             admin(matches, bucket, object, &client)
@@ -220,14 +444,20 @@ pub fn commands<P, D>(matches: &ArgMatches, cmd: Commands, client: &mut Client<P
     Ok(())
 }
 
-fn cmd_get<P, D>(bucket: &str, object: &str, path: &str, client: &Client<P, D>) -> Result<(), S3Error>
+// NB: A resumed download always continues in a single stream even if parallel was asked for.
+pub fn cmd_get<P, D>(bucket: &str, object: &str, path: &str, is_parallel: bool, is_resume: bool, client: &Client<P, D>)
+                 -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
     if client.is_time {
         let mut operation: Operation;
         operation = Operation::default();
-        let result = get_object(bucket, &object, &path, Some(&mut operation), client);
+        let result = if is_parallel && !is_resume {
+            get_object_parallel(bucket, &object, &path, Some(&mut operation), client)
+        } else {
+            get_object(bucket, &object, &path, is_resume, Some(&mut operation), client)
+        };
         match client.output.format {
             OutputFormat::Serialize => {
                 // Could have already been serialized before being passed to this function.
@@ -249,17 +479,24 @@ fn cmd_get<P, D>(bucket: &str, object: &str, path: &str, client: &Client<P, D>)
             },
             _ => {},
         }
+        result
+    } else if is_parallel && !is_resume {
+        get_object_parallel(bucket, &object, &path, None, client)
     } else {
-        let result = get_object(bucket, &object, &path, None, client);
+        get_object(bucket, &object, &path, is_resume, None, client)
     }
-
-    Ok(())
 }
 
-fn cmd_put<P, D>(bucket: &str, object: &str, path: &str, part_size: u64, client: &Client<P, D>) -> Result<(), S3Error>
+pub fn cmd_put<P, D>(bucket: &str, object: &str, path: &str, part_size: u64, client: &Client<P, D>) -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
+    // A single PUT can not go past 5GB so larger files always go up as multipart.
+    let part_size = match fs::metadata(path) {
+        Ok(ref metadata) if part_size < PART_SIZE_MIN && metadata.len() > PUT_SIZE_MAX => PART_SIZE_MIN,
+        _ => part_size,
+    };
+
     if client.is_time {
         let mut operation: Operation;
         operation = Operation::default();
@@ -286,18 +523,373 @@ fn cmd_put<P, D>(bucket: &str, object: &str, path: &str, part_size: u64, client:
                 },
                 _ => {},
             }
+            result
         } else {
-            let result = put_multipart_upload(bucket, &object, path, part_size, client);
+            put_multipart_upload(bucket, &object, path, part_size, client)
         }
     } else if part_size < PART_SIZE_MIN {
-        let result = put_object(bucket, &object, path, None, client);
+        put_object(bucket, &object, path, None, client)
+    } else {
+        put_multipart_upload(bucket, &object, path, part_size, client)
+    }
+}
+
+/// Uploads every file under dir to prefix keeping the relative paths. Transfer state files
+/// left by interrupted uploads/downloads are skipped. With is_move each file is removed once its
+/// upload has been verified.
+fn cmd_put_recursive<P, D>(bucket: &str, prefix: &str, dir: &str, part_size: u64, filter: &KeyFilter, is_move: bool,
+                           client: &Client<P, D>)
+                           -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let root = Path::new(dir);
+    let files = match walk_files(root) {
+        Ok(files) => files,
+        Err(e) => {
+            let error = format!("Error reading directory {}: {}", dir, e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let mut count: usize = 0;
+    let mut failed: usize = 0;
+
+    let files = files.iter().filter(|file| !is_transfer_state_file(file) && filter.is_match(&relative_key(root, file)));
+
+    for file in files {
+        let key = format!("{}{}", prefix_dir(prefix), relative_key(root, file));
+        count += 1;
+        let result = if is_move {
+            move_put(bucket, &key, &file.to_string_lossy(), part_size, client)
+        } else {
+            cmd_put(bucket, &key, &file.to_string_lossy(), part_size, client)
+        };
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    transfer_summary(count, failed, is_move, client)
+}
+
+/// Downloads every object under prefix into dir keeping the relative paths. With is_move each
+/// object is deleted once its download has been verified.
+fn cmd_get_recursive<P, D>(bucket: &str, prefix: &str, dir: &str, is_parallel: bool, is_resume: bool,
+                           filter: &KeyFilter, is_move: bool, client: &Client<P, D>)
+                           -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    // The prefix is treated as a folder so s3://bucket/logs does not also pick up logs2/...
+    let prefix = prefix_dir(prefix);
+    let objects = match s3request::list_all_objects(client.s3client.endpoint(), bucket, &prefix) {
+        Ok(objects) => objects,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let root = Path::new(if dir.is_empty() { "." } else { dir });
+    let mut count: usize = 0;
+    let mut failed: usize = 0;
+
+    // Keys ending in '/' are folder placeholders and have nothing to download.
+    let objects = objects.iter()
+        .filter(|object| !object.key.ends_with('/') && filter.is_match(&object.key[prefix.len()..]));
+
+    for object in objects {
+        count += 1;
+        let path = match local_path(root, &object.key[prefix.len()..]) {
+            Some(path) => path,
+            None => {
+                println_color_quiet!(client.is_quiet,
+                                     client.error.color,
+                                     "Skipping {}: the key would be written outside of {}",
+                                     object.key,
+                                     root.display());
+                failed += 1;
+                continue;
+            },
+        };
+
+        if let Some(parent) = path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                println_color_quiet!(client.is_quiet, client.error.color, "Error creating {}: {}", parent.display(), e);
+                failed += 1;
+                continue;
+            }
+        }
+
+        let result = if is_move {
+            move_get(bucket, &object.key, &path.to_string_lossy(), is_parallel, is_resume, client)
+        } else {
+            cmd_get(bucket, &object.key, &path.to_string_lossy(), is_parallel, is_resume, client)
+        };
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    transfer_summary(count, failed, is_move, client)
+}
+
+/// Server side copy of every object under src_prefix to prefix keeping the relative keys. With
+/// is_move each source object is deleted once its copy has been verified.
+fn cmd_copy_recursive<P, D>(src_bucket: &str, src_prefix: &str, bucket: &str, prefix: &str, filter: &KeyFilter,
+                            is_move: bool, client: &Client<P, D>)
+                            -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() || src_bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let src_prefix = prefix_dir(src_prefix);
+    let objects = match s3request::list_all_objects(client.s3client.endpoint(), src_bucket, &src_prefix) {
+        Ok(objects) => objects,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let mut count: usize = 0;
+    let mut failed: usize = 0;
+
+    let objects = objects.iter()
+        .filter(|object| !object.key.ends_with('/') && filter.is_match(&object.key[src_prefix.len()..]));
+
+    for object in objects {
+        let key = format!("{}{}", prefix_dir(prefix), &object.key[src_prefix.len()..]);
+        count += 1;
+        let result = if is_move {
+            move_copy(src_bucket, &object.key, bucket, &key, client)
+        } else {
+            copy_object(src_bucket, &object.key, bucket, &key, client)
+        };
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    transfer_summary(count, failed, is_move, client)
+}
+
+// Prefix with a trailing '/' (unless empty) so relative paths can be appended.
+pub fn prefix_dir(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+// Files written by s3lsio itself to resume transfers.
+pub fn is_transfer_state_file(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".s3lsio-part") || name.ends_with(".s3lsio-download") || name.ends_with(".s3lsio-upload")
+}
+
+// Reports how a multi-file transfer went and fails if any file did.
+fn transfer_summary<P, D>(count: usize, failed: usize, is_move: bool, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if failed > 0 {
+        let error = format!("{} of {} files failed to {}", failed, count, if is_move { "move" } else { "copy" });
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet,
+                             client.output.color,
+                             "{} files {}",
+                             count,
+                             if is_move { "moved" } else { "copied" });
+    }
+
+    Ok(())
+}
+
+/// Uploads a file and removes it once a HEAD of the new object matches its size (and MD5 when it
+/// went up in a single PUT).
+fn move_put<P, D>(bucket: &str, key: &str, path: &str, part_size: u64, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            let error = format!("Error reading file {}: {}", path, e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    // Same key the put itself ends up using.
+    let key = if key.is_empty() || key.ends_with('/') {
+        format!("{}{}", key, Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(""))
+    } else {
+        key.to_string()
+    };
+
+    try!(cmd_put(bucket, &key, path, part_size, client));
+
+    // Multipart ETags are not an MD5 of the file so only the size can be checked for those.
+    let e_tag = if part_size < PART_SIZE_MIN && size <= PUT_SIZE_MAX {
+        file_md5(Path::new(path)).unwrap_or(String::new())
     } else {
-        let result = put_multipart_upload(bucket, &object, path, part_size, client);
+        String::new()
+    };
+
+    try!(verify_object(bucket, &key, size, &e_tag, client));
+
+    if let Err(e) = fs::remove_file(path) {
+        let error = format!("Uploaded s3://{}/{} but could not remove {}: {}", bucket, key, path, e);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
     }
 
     Ok(())
 }
 
+/// Downloads an object and deletes it once the local file has the size from a HEAD of the object
+/// and a second HEAD shows the object (size and ETag) did not change during the download.
+fn move_get<P, D>(bucket: &str, key: &str, path: &str, is_parallel: bool, is_resume: bool, client: &Client<P, D>)
+                  -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let (size, e_tag, _) = match head_object_info(bucket, key, client) {
+        Ok(info) => info,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    // Moving into a directory (or with no path at all) keeps the object name.
+    let name = key.rsplit('/').next().unwrap_or(key);
+    let path = if path.is_empty() {
+        name.to_string()
+    } else if path.ends_with('/') || Path::new(path).is_dir() {
+        Path::new(path).join(name).to_string_lossy().into_owned()
+    } else {
+        path.to_string()
+    };
+
+    try!(cmd_get(bucket, key, &path, is_parallel, is_resume, client));
+
+    let local_size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+    if local_size != size {
+        let error = format!("Verify failed: {} is {} bytes but s3://{}/{} is {} bytes",
+                            path,
+                            local_size,
+                            bucket,
+                            key,
+                            size);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    try!(verify_object(bucket, key, size, &e_tag, client));
+
+    delete_object(bucket, key, "", None, client)
+}
+
+/// Server side copy that deletes the source once a HEAD of the copy matches it.
+fn move_copy<P, D>(src_bucket: &str, src_key: &str, bucket: &str, key: &str, client: &Client<P, D>)
+                   -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if src_bucket == bucket && src_key == copy_key(src_key, key) {
+        let error = format!("Source and destination are the same object");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let (size, e_tag, _) = match head_object_info(src_bucket, src_key, client) {
+        Ok(info) => info,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    try!(copy_object(src_bucket, src_key, bucket, key, client));
+    try!(verify_object(bucket, &copy_key(src_key, key), size, &e_tag, client));
+
+    delete_object(src_bucket, src_key, "", None, client)
+}
+
+// HEADs the object and checks it has the expected size and ETag. ETags are skipped when either one
+// is empty or from a multipart upload (contains '-') since those are not comparable. They are also
+// skipped for SSE-KMS and SSE-C objects whose ETag is not an MD5 of the data.
+fn verify_object<P, D>(bucket: &str, key: &str, size: u64, e_tag: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut request = S3Request::new("HEAD", bucket, key);
+    request.headers = client.sse_customer_headers.clone();
+
+    let error = match s3request::send(client.s3client.endpoint(), &request) {
+        Ok(response) => {
+            let object_size = response.header("Content-Length").and_then(|len| len.parse::<u64>().ok()).unwrap_or(0);
+            let object_e_tag = response.header("ETag").unwrap_or("").trim_matches('"');
+            let e_tag = e_tag.trim_matches('"');
+            let is_md5 = is_md5_e_tag(&response, client);
+
+            if object_size != size {
+                format!("Verify failed: s3://{}/{} is {} bytes, expected {}", bucket, key, object_size, size)
+            } else if is_md5 && !e_tag.is_empty() && !object_e_tag.is_empty() && !e_tag.contains('-') &&
+                      !object_e_tag.contains('-') && e_tag != object_e_tag {
+                format!("Verify failed: s3://{}/{} has ETag {}, expected {}", bucket, key, object_e_tag, e_tag)
+            } else {
+                return Ok(());
+            }
+        },
+        Err(e) => format!("Verify failed: {:#?}", e),
+    };
+
+    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+    Err(S3Error::new(error))
+}
+
+/// True when the ETag of a HEAD response can be an MD5 of the data: the object is unencrypted or
+/// SSE-S3 (AES256). SSE-KMS and SSE-C objects have ETags that are not.
+pub fn is_md5_e_tag<P, D>(response: &S3Response, client: &Client<P, D>) -> bool
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let encryption = response.header("x-amz-server-side-encryption").unwrap_or("");
+    (encryption.is_empty() || encryption == "AES256") && client.sse_customer_headers.is_empty() &&
+    response.header("x-amz-server-side-encryption-customer-algorithm").is_none()
+}
+
 fn ver<P, D>(matches: &ArgMatches,
              client: &Client<P, D>)
              -> Result<(), S3Error>
@@ -330,8 +922,17 @@ fn acl<P, D>(matches: &ArgMatches,
 {
     match matches.subcommand() {
         ("set", Some(matches)) => {
-            let (bucket, object, last) = find_bucket_object_last(&matches);
-            set_bucket_acl(matches, bucket, client)
+            // With only --grant-* options the bucket ends up in the acls position.
+            let (canned, target) = match (matches.value_of("acls"), matches.value_of("bucket")) {
+                (Some(acls), None) if acls.starts_with("s3://") => ("", acls),
+                (acls, bucket) => (acls.unwrap_or(""), bucket.unwrap_or("")),
+            };
+            let target = if target.starts_with("s3://") { &target[5..] } else { target };
+            let (bucket, object) = match target.find('/') {
+                Some(index) => (&target[..index], &target[index + 1..]),
+                None => (target, ""),
+            };
+            set_acl(matches, canned, bucket, object, client)
         },
         // Fall through to `get`
         (_, Some(matches)) => {
@@ -405,6 +1006,78 @@ fn delete_bucket<P, D>(bucket: &str, client: &Client<P, D>) -> Result<(), S3Erro
     }
 }
 
+/// Empties the bucket and then deletes it. In-progress multipart uploads are aborted and every
+/// object version and delete marker is removed (followed by a plain listing for stores that do not
+/// support versions) with Multi-Object Delete. Asks first unless --yes was given.
+fn force_delete_bucket<P, D>(bucket: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    try!(confirm_action(&format!("delete bucket {} and ALL of its objects, versions and uploads", bucket),
+                        client));
+
+    let endpoint = client.s3client.endpoint();
+
+    let uploads = match s3request::list_all_uploads(endpoint, bucket, "") {
+        Ok(uploads) => uploads,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    for &(ref key, ref upload_id) in &uploads {
+        let mut request = MultipartUploadAbortRequest::default();
+        request.bucket = bucket.to_string();
+        request.upload_id = upload_id.clone();
+        request.key = key.clone();
+
+        if let Err(e) = client.s3client.multipart_upload_abort(&request) {
+            let error = format!("Error aborting upload {} of s3://{}/{}: {:#?}", upload_id, bucket, key, e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        }
+    }
+
+    let versions: Vec<(String, String)> = match s3request::list_all_versions(endpoint, bucket, "") {
+        Ok(versions) => versions.into_iter().map(|version| (version.key, version.version_id)).collect(),
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+    try!(delete_batches(bucket, &versions, client));
+
+    let objects: Vec<(String, String)> = match s3request::list_all_objects(endpoint, bucket, "") {
+        Ok(objects) => objects.into_iter().map(|object| (object.key, String::new())).collect(),
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+    try!(delete_batches(bucket, &objects, client));
+
+    if (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet,
+                             client.output.color,
+                             "{} uploads aborted, {} versions and {} objects deleted",
+                             uploads.len(),
+                             versions.len(),
+                             objects.len());
+    }
+
+    delete_bucket(bucket, client)
+}
+
 // Get functions...
 fn get_bucket_head<P, D>(bucket: &str, client: &Client<P, D>) -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
@@ -573,7 +1246,8 @@ fn get_buckets_list<P, D>(client: &Client<P, D>) -> Result<(), S3Error>
 }
 
 // Set functions...
-fn set_bucket_acl<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D>) -> Result<(), S3Error>
+fn set_acl<P, D>(matches: &ArgMatches, canned: &str, bucket: &str, object: &str, client: &Client<P, D>)
+                 -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
@@ -583,64 +1257,38 @@ fn set_bucket_acl<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D
         return Err(S3Error::new(error));
     }
 
-    let acl: CannedAcl;
-    let cli_acl = matches.value_of("acls").unwrap_or("").to_string().to_lowercase();
-
-    match cli_acl.as_ref() {
-        "public-read" => acl = CannedAcl::PublicRead,
-        "public-rw" | "public-readwrite" => acl = CannedAcl::PublicReadWrite,
-        "private" => acl = CannedAcl::Private,
-        _ => {
-            println_color_quiet!(client.is_quiet,
-                                 client.error.color,
-                                 "missing acl: public-read, public-rw, public-readwrite or private");
-            return Err(S3Error::new("missing acl: public-read, public-rw, public-readwrite or private"));
+    let headers = match acl_headers(canned, matches) {
+        Ok(ref headers) if headers.is_empty() => {
+            let error = format!("missing acl: {} and/or --grant-* options", CANNED_ACLS.join(", "));
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
         },
-    }
-
-    let mut request = PutBucketAclRequest::default();
-    request.bucket = bucket.to_string();
+        Ok(headers) => headers,
+        Err(e) => {
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", e);
+            return Err(S3Error::new(e));
+        },
+    };
 
-    // get acl option...
-    request.acl = Some(acl);
+    // The SDK only sends canned ACLs so the grant headers need a direct request.
+    let mut request = S3Request::new("PUT", bucket, object);
+    request.subresources.push(("acl".to_string(), String::new()));
+    request.headers = headers;
 
-    match client.s3client.put_bucket_acl(&request) {
-        Ok(output) => {
-            let acl = get_bucket_acl(bucket, client);
-            if let Ok(acl) = acl {
-                match client.output.format {
-                    OutputFormat::Serialize => {
-                        // Could have already been serialized before being passed to this function.
-                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", acl);
-                    },
-                    OutputFormat::Plain => {
-                        // Could have already been serialized before being passed to this function.
-                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", acl);
-                    },
-                    OutputFormat::JSON => {
-                        println_color_quiet!(client.is_quiet,
-                                             client.output.color,
-                                             "{}",
-                                             json::encode(&acl).unwrap_or("{}".to_string()));
-                    },
-                    OutputFormat::PrettyJSON => {
-                        println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&acl));
-                    },
-                    OutputFormat::Simple => {
-                        println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&acl));
-                    },
-                    _ => {},
-                }
+    match s3request::send(client.s3client.endpoint(), &request) {
+        Ok(_) => {
+            if object.is_empty() {
+                get_bucket_acl(bucket, client)
+            } else {
+                get_object_acl(bucket, object, client)
             }
         },
         Err(e) => {
             let error = format!("{:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-            return Err(S3Error::new(error));
+            Err(S3Error::new(error))
         },
     }
-
-    Ok(())
 }
 
 fn set_bucket_versioning<P, D>(matches: &ArgMatches, bucket: &str, client: &Client<P, D>) -> Result<(), S3Error>
@@ -684,7 +1332,14 @@ fn set_bucket_versioning<P, D>(matches: &ArgMatches, bucket: &str, client: &Clie
 }
 
 // Objects...
-fn get_object_list<P, D>(bucket: &str, prefix: &str, list_version: u16, client: &Client<P, D>) -> Result<(), S3Error>
+/// Lists every object under prefix (up to max_keys when given) following V1 markers or V2
+/// continuation tokens page by page. Simple and Plain output is printed as each page arrives.
+///
+/// With a delimiter only the objects directly under prefix are listed and everything deeper is
+/// rolled up into common prefixes which are shown like directories.
+fn get_object_list<P, D>(bucket: &str, prefix: &str, delimiter: &str, list_version: u16, max_keys: Option<u64>,
+                         filter: &KeyFilter, client: &Client<P, D>)
+                         -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
@@ -694,52 +1349,111 @@ fn get_object_list<P, D>(bucket: &str, prefix: &str, list_version: u16, client:
         return Err(S3Error::new(error));
     }
 
-    let mut request = ListObjectsRequest::default();
-    request.bucket = bucket.to_string();
-    if !prefix.is_empty() {
-        request.prefix = Some(prefix.to_string());
+    // Each page is printed as it arrives in the ListObjectsOutput layout ls has always used. JSON
+    // and PrettyJSON are both one compact document per line (JSON Lines) so a listing of any size
+    // can be streamed and still parsed.
+    let mut marker = String::new();
+    let mut remaining = max_keys;
+
+    loop {
+        let page_keys = remaining.map(|remaining| cmp::min(remaining, 1000));
+        let mut page = match s3request::list_objects_page(client.s3client.endpoint(),
+                                                      bucket,
+                                                      prefix,
+                                                      delimiter,
+                                                      &marker,
+                                                      page_keys,
+                                                      list_version) {
+            Ok(page) => page,
+            Err(error) => {
+                let format = format!("{:#?}", error);
+                let error = S3Error::new(format);
+                println_color_quiet!(client.is_quiet, client.error.color, "{:#?}", error);
+                return Err(error);
+            },
+        };
+
+        // Filtering is on the client so max-keys is a cap on what passes the filter.
+        page.objects.retain(|object| filter.is_match(&object.key[prefix.len()..]));
+        page.common_prefixes.retain(|common_prefix| filter.is_match(&common_prefix[prefix.len()..]));
+
+        match client.output.format {
+            OutputFormat::Simple => {
+                for common_prefix in &page.common_prefixes {
+                    println_color_quiet!(client.is_quiet, client.output.color, "s3://{}/{}", bucket, common_prefix);
+                }
+                for object in &page.objects {
+                    println_color_quiet!(client.is_quiet, client.output.color, "s3://{}/{}", bucket, object.key);
+                }
+            },
+            OutputFormat::None | OutputFormat::NoneAll => {},
+            _ => {
+                let output = list_objects_output(bucket, prefix, delimiter, &marker, page_keys, list_version, &page);
+                match client.output.format {
+                    OutputFormat::Serialize => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                    },
+                    OutputFormat::Plain => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                    },
+                    OutputFormat::JSON | OutputFormat::PrettyJSON => {
+                        println_color_quiet!(client.is_quiet,
+                                             client.output.color,
+                                             "{}",
+                                             json::encode(&output).unwrap_or("{}".to_string()));
+                    },
+                    _ => {},
+                }
+            },
+        }
+
+        // Common prefixes count against max-keys the same as objects do.
+        let page_count = (page.objects.len() + page.common_prefixes.len()) as u64;
+        remaining = remaining.map(|remaining| remaining.saturating_sub(page_count));
+
+        if !page.is_truncated || page.next_marker.is_empty() || remaining == Some(0) {
+            break;
+        }
+        marker = page.next_marker;
     }
+
+    Ok(())
+}
+
+// One listing page in the SDK's ListObjectsOutput layout.
+fn list_objects_output(bucket: &str, prefix: &str, delimiter: &str, marker: &str, max_keys: Option<u64>,
+                       list_version: u16, page: &ObjectPage)
+                       -> ListObjectsPage {
+    let mut output = ListObjectsPage::default();
+    output.name = bucket.to_string();
+    output.prefix = prefix.to_string();
+    output.delimiter = delimiter.to_string();
+    output.max_keys = max_keys.unwrap_or(1000);
+    output.is_truncated = page.is_truncated;
     if list_version == 2 {
-        request.version = Some(2);
-    } // default to original version of list bucket
+        output.continuation_token = marker.to_string();
+        output.next_continuation_token = page.next_marker.clone();
+        output.key_count = (page.objects.len() + page.common_prefixes.len()) as u64;
+    } else {
+        output.marker = marker.to_string();
+        output.next_marker = page.next_marker.clone();
+    }
 
-    match client.s3client.list_objects(&request) {
-        Ok(output) => {
-            match client.output.format {
-                OutputFormat::Serialize => {
-                    // Could have already been serialized before being passed to this function.
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::Plain => {
-                    // Could have already been serialized before being passed to this function.
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::JSON => {
-                    println_color_quiet!(client.is_quiet,
-                                         client.output.color,
-                                         "{}",
-                                         json::encode(&output).unwrap_or("{}".to_string()));
-                },
-                OutputFormat::PrettyJSON => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&output));
-                },
-                OutputFormat::Simple => {
-                    for object in output.contents {
-                        println_color_quiet!(client.is_quiet, client.output.color, "s3://{}/{}", bucket, object.key);
-                    }
-                },
-                _ => {},
-            }
+    for object in &page.objects {
+        let mut metadata = ListObjectsEntry::default();
+        metadata.key = object.key.clone();
+        metadata.size = object.size;
+        metadata.e_tag = object.e_tag.clone();
+        metadata.last_modified = object.last_modified.clone();
+        metadata.storage_class = object.storage_class.clone();
+        output.contents.push(metadata);
+    }
 
-            Ok(())
-        },
-        Err(error) => {
-            let format = format!("{:#?}", error);
-            let error = S3Error::new(format);
-            println_color_quiet!(client.is_quiet, client.error.color, "{:#?}", error);
-            Err(error)
-        },
+    for common_prefix in &page.common_prefixes {
+        output.common_prefixes.push(CommonPrefix { prefix: common_prefix.clone() });
     }
+
+    output
 }
 
 fn get_object_version_list<P, D>(bucket: &str,
@@ -882,8 +1596,8 @@ fn get_object_multipart_list<P, D>(bucket: &str, upload_id: &str, key: &str, cli
     }
 }
 
-// Limited in file size.
-fn get_object<P, D>(bucket: &str, object: &str, path: &str, operation: Option<&mut Operation>, client: &Client<P, D>)
+fn get_object<P, D>(bucket: &str, object: &str, path: &str, is_resume: bool, operation: Option<&mut Operation>,
+                    client: &Client<P, D>)
                     -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
@@ -904,175 +1618,728 @@ fn get_object<P, D>(bucket: &str, object: &str, path: &str, operation: Option<&m
     request.bucket = bucket.to_string();
     request.key = object.to_string();
 
-    object_get(&request, path, operation, client)
+    object_get(&request, path, is_resume, operation, client)
 }
 
-// Common portion of get_object... functions
-fn object_get<P, D>(request: &GetObjectRequest, path: &str, operation: Option<&mut Operation>, client: &Client<P, D>)
+// Common portion of get_object... functions. The body is streamed to a temporary file next to
+// path in GET_CHUNK_SIZE ranges and only renamed into place once the whole object has arrived.
+// A failed download leaves the temporary file behind so is_resume can continue it later.
+fn object_get<P, D>(request: &GetObjectRequest, path: &str, is_resume: bool, operation: Option<&mut Operation>,
+                    client: &Client<P, D>)
                     -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
-    match client.s3client.get_object(&request, operation) {
-        Ok(output) => {
-            // NoneAll means no writing to disk or stdout
-            if client.output.format != OutputFormat::NoneAll {
-                let mut file = File::create(path).unwrap();
-                match file.write_all(output.get_body()) {
-                    Ok(_) => {
-                        // NOTE: Need to remove body from output (after it writes out) by making it mut so that
-                        // items below can output metadata OR place body in different element than others.
-                        match client.output.format {
-                            // NOTE: Operation use of SteadyTime can be (by default) be serialized w/o effort :(
-                            OutputFormat::Serialize => {
-                                println_color_quiet!(client.is_quiet, client.output.color, "Success");
-                            },
-                            OutputFormat::Plain => {
-                                println_color_quiet!(client.is_quiet, client.output.color, "Success");
-                            },
-                            OutputFormat::JSON => {
-                                println_color_quiet!(client.is_quiet, client.output.color, "Success");
-                            },
-                            OutputFormat::PrettyJSON => {
-                                println_color_quiet!(client.is_quiet, client.output.color, "Success");
-                            },
-                            OutputFormat::Simple => {
-                                println_color_quiet!(client.is_quiet, client.output.color, "Success");
-                            },
-                            _ => {},
-                        }
-                        Ok(())
+    // NoneAll means no writing to disk or stdout
+    let is_write = client.output.format != OutputFormat::NoneAll;
+    let temp_path = download_temp_path(path);
+    let state_path = download_state_path(path);
+    // Ranges asked for by the caller are not worth resuming.
+    let is_keep = is_write && request.range.is_none();
+
+    let resume = if is_resume && is_keep {
+        download_resume_point(request, &temp_path, &state_path, client)
+    } else {
+        None
+    };
+
+    if let Some((offset, _)) = resume {
+        if client.output.format != OutputFormat::None {
+            println_color_quiet!(client.is_quiet, client.output.color, "Resuming {} at byte {}", path, offset);
+        }
+    }
+
+    let mut file = if is_write {
+        let file = if resume.is_some() {
+            OpenOptions::new().append(true).open(&temp_path)
+        } else {
+            File::create(&temp_path)
+        };
+
+        match file {
+            Ok(file) => Some(file),
+            Err(e) => {
+                let error = format!("{:#?}", e);
+                println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                return Err(S3Error::new(error));
+            },
+        }
+    } else {
+        None
+    };
+
+    let result = object_get_chunks(request,
+                                   file.as_mut(),
+                                   resume,
+                                   if is_keep { Some(&state_path) } else { None },
+                                   operation,
+                                   client);
+    // Close the file before it is renamed or removed.
+    drop(file);
+
+    match result {
+        Ok(_) => {
+            if is_write {
+                if let Err(e) = fs::rename(&temp_path, path) {
+                    let error = format!("{:#?}", e);
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                    return Err(S3Error::new(error));
+                }
+                // Ranged downloads do not save any state.
+                if let Err(e) = fs::remove_file(&state_path) {
+                    if e.kind() != io::ErrorKind::NotFound {
+                        println_color_quiet!(client.is_quiet,
+                                             client.error.color,
+                                             "Error removing {}: {}",
+                                             state_path,
+                                             e);
+                    }
+                }
+
+                // NOTE: Need to remove body from output (after it writes out) by making it mut so that
+                // items below can output metadata OR place body in different element than others.
+                match client.output.format {
+                    // NOTE: Operation use of SteadyTime can be (by default) be serialized w/o effort :(
+                    OutputFormat::Serialize => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Success");
+                    },
+                    OutputFormat::Plain => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Success");
+                    },
+                    OutputFormat::JSON => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Success");
+                    },
+                    OutputFormat::PrettyJSON => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Success");
                     },
-                    Err(e) => {
-                        let error = format!("{:#?}", e);
-                        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-                        Err(S3Error::new(error))
+                    OutputFormat::Simple => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "Success");
                     },
+                    _ => {},
                 }
-            } else {
-                Ok(())
             }
+            Ok(())
         },
         Err(e) => {
             let error = format!("{:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            if is_keep {
+                println_color_quiet!(client.is_quiet,
+                                     client.error.color,
+                                     "Partial download kept in {}. Run the same command with --resume to continue",
+                                     temp_path);
+            } else if is_write {
+                if let Err(e) = fs::remove_file(&temp_path) {
+                    println_color_quiet!(client.is_quiet, client.error.color, "Error removing {}: {}", temp_path, e);
+                }
+            }
             Err(S3Error::new(error))
         },
     }
 }
 
-fn get_object_range<P, D>(bucket: &str, object: &str, offset: u64, len: u64, path: &str,
-                          operation: Option<&mut Operation>, client: &Client<P, D>)
-                          -> Result<(), S3Error>
+// Works out where an interrupted download left off. The partial file is only used if the object
+// still has the ETag and Last-Modified it had when the download started.
+fn download_resume_point<P, D>(request: &GetObjectRequest, temp_path: &str, state_path: &str, client: &Client<P, D>)
+                               -> Option<(u64, String)>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
-    if bucket.is_empty() {
-        let error = format!("Bucket was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
-    }
+    let state = match load_state::<DownloadState>(state_path) {
+        Some(state) => state,
+        None => return None,
+    };
 
-    if object.is_empty() {
-        let error = format!("Object was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
+    if state.bucket != request.bucket || state.key != request.key {
+        return None;
     }
 
-    let mut request = GetObjectRequest::default();
-    request.bucket = bucket.to_string();
-    request.key = object.to_string();
-    request.range = Some(format!("bytes={}-{}", offset, len));
+    let offset = match fs::metadata(temp_path) {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return None,
+    };
 
-    object_get(&request, path, operation, client)
+    match head_object_info(&request.bucket, &request.key, client) {
+        Ok((size, e_tag, last_modified)) => {
+            if e_tag == state.e_tag && last_modified == state.last_modified && offset <= size {
+                Some((offset, e_tag))
+            } else {
+                if client.output.format != OutputFormat::None {
+                    println_color_quiet!(client.is_quiet,
+                                         client.output.color,
+                                         "{} changed since the partial download, starting over",
+                                         request.key);
+                }
+                None
+            }
+        },
+        Err(_) => None,
+    }
 }
 
-fn get_object_head<P, D>(bucket: &str, object: &str, client: &Client<P, D>) -> Result<(), S3Error>
+// Fetches the object one range at a time and appends each chunk to file (if there is one) so only
+// a single chunk is ever held in memory. resume is the offset and ETag of an earlier partial
+// download. The ETag and Last-Modified of a new download are recorded in state_path.
+fn object_get_chunks<P, D>(request: &GetObjectRequest,
+                           mut file: Option<&mut File>,
+                           resume: Option<(u64, String)>,
+                           state_path: Option<&str>,
+                           operation: Option<&mut Operation>,
+                           client: &Client<P, D>)
+                           -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
-    if bucket.is_empty() {
-        let error = format!("Bucket was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
+    // A range asked for by the caller is fetched as is.
+    if request.range.is_some() {
+        let output = try!(get_object_output(request, operation, client));
+        if let Some(ref mut file) = file {
+            try!(file.write_all(output.get_body()).map_err(|e| S3Error::new(format!("{}", e))));
+        }
+        return Ok(());
     }
 
-    if object.is_empty() {
-        let error = format!("Object was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
-    }
+    let mut operation = operation;
+    let mut chunk_request = request.clone();
+    let (mut offset, mut e_tag) = resume.unwrap_or((0, String::new()));
+    let start = offset;
 
-    let mut request = HeadObjectRequest::default();
-    request.bucket = bucket.to_string();
-    request.key = object.to_string();
+    loop {
+        chunk_request.range = Some(format!("bytes={}-{}", offset, offset + GET_CHUNK_SIZE - 1));
 
-    match client.s3client.head_object(&request) {
-        Ok(output) => {
-            match client.output.format {
-                OutputFormat::Serialize => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::Plain => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::JSON => {
-                    println_color_quiet!(client.is_quiet,
-                                         client.output.color,
-                                         "{}",
-                                         json::encode(&output).unwrap_or("{}".to_string()));
-                },
-                OutputFormat::PrettyJSON => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&output));
-                },
-                OutputFormat::Simple => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                _ => {},
+        let mut chunk_operation = Operation::default();
+        let output = match get_object_output(&chunk_request, Some(&mut chunk_operation), client) {
+            Ok(output) => output,
+            // Zero byte objects have no satisfiable range so fetch them without one.
+            Err(ref e) if offset == 0 && e.aws.code == "InvalidRange" => {
+                return get_object_output(request, operation, client).map(|_| ());
+            },
+            // The partial file already holds the whole object.
+            Err(ref e) if offset == start && e.aws.code == "InvalidRange" => break,
+            Err(e) => return Err(e),
+        };
+
+        if let Some(ref mut operation) = operation {
+            add_operation(operation, chunk_operation);
+        }
+
+        if e_tag.is_empty() {
+            e_tag = output.e_tag.clone();
+            if let Some(state_path) = state_path {
+                let state = DownloadState {
+                    bucket: request.bucket.clone(),
+                    key: request.key.clone(),
+                    e_tag: output.e_tag.clone(),
+                    last_modified: output.last_modified.clone(),
+                };
+                // Without the state file a later --resume starts over instead of failing the download.
+                let _ = save_state(state_path, &state);
             }
-            Ok(())
-        },
-        Err(e) => {
-            let error = format!("{:#?}", e);
-            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-            Err(S3Error::new(error))
-        },
+        } else if output.e_tag != e_tag {
+            return Err(S3Error::new(format!("Object {} changed while downloading", request.key)));
+        }
+
+        // Servers that ignore the Range header send the whole object back with no Content-Range.
+        let size = content_range_size(&output.content_range);
+        if size.is_none() && start > 0 {
+            return Err(S3Error::new("Server does not support byte ranges so the download can not be resumed"));
+        }
+
+        let body = output.get_body();
+        if let Some(ref mut file) = file {
+            try!(file.write_all(body).map_err(|e| S3Error::new(format!("{}", e))));
+        }
+        offset += body.len() as u64;
+
+        match size {
+            Some(size) if offset < size && !body.is_empty() => {},
+            _ => break,
+        }
     }
+
+    Ok(())
 }
 
-fn get_object_acl<P, D>(bucket: &str, object: &str, client: &Client<P, D>) -> Result<(), S3Error>
+// GET Object through the SDK unless there is an SSE-C key which the SDK can not send.
+fn get_object_output<P, D>(request: &GetObjectRequest, operation: Option<&mut Operation>, client: &Client<P, D>)
+                           -> Result<GetObjectOutput, S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
-    if bucket.is_empty() {
-        let error = format!("Bucket was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
+    if client.sse_customer_headers.is_empty() {
+        client.s3client.get_object(request, operation)
+    } else {
+        get_object_sse(client.s3client.endpoint(), request, &client.sse_customer_headers)
     }
+}
 
-    if object.is_empty() {
-        let error = format!("Object was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
+// GET Object sent directly with the SSE-C headers. Only the output fields the downloads use are
+// filled in.
+fn get_object_sse(endpoint: &Endpoint, input: &GetObjectRequest, headers: &[(String, String)])
+                  -> Result<GetObjectOutput, S3Error> {
+    let mut request = S3Request::new("GET", &input.bucket, &input.key);
+    request.headers = headers.to_vec();
+    if let Some(ref range) = input.range {
+        request.headers.push(("Range".to_string(), range.clone()));
     }
 
-    let mut request = GetObjectAclRequest::default();
-    request.bucket = bucket.to_string();
-    request.key = object.to_string();
+    let response = try!(s3request::send(endpoint, &request));
+
+    let mut output = GetObjectOutput::default();
+    {
+        let header = |name: &str| response.header(name).unwrap_or("").to_string();
+        output.e_tag = header("ETag");
+        output.last_modified = header("Last-Modified");
+        output.content_range = header("Content-Range");
+        output.content_type = header("Content-Type");
+        output.sse_customer_algorithm = header("x-amz-server-side-encryption-customer-algorithm");
+        output.sse_customer_key_md5 = header("x-amz-server-side-encryption-customer-key-MD5");
+    }
+    output.body_buffer = response.body;
 
-    match client.s3client.get_object_acl(&request) {
-        Ok(output) => {
-            match client.output.format {
-                OutputFormat::Serialize => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::Plain => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
-                },
-                OutputFormat::JSON => {
-                    println_color_quiet!(client.is_quiet,
-                                         client.output.color,
-                                         "{}",
+    Ok(output)
+}
+
+// Size, ETag and Last-Modified of an object. HEAD is sent directly since the SDK can not parse a
+// Content-Length over 2GB.
+fn head_object_info<P, D>(bucket: &str, key: &str, client: &Client<P, D>) -> Result<(u64, String, String), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let response = try!(head_object_response(bucket, key, client));
+
+    Ok((response_size(&response),
+        response.header("ETag").unwrap_or("").to_string(),
+        response.header("Last-Modified").unwrap_or("").to_string()))
+}
+
+// HEAD Object (with any SSE-C headers) for callers that need more than head_object_info returns.
+fn head_object_response<P, D>(bucket: &str, key: &str, client: &Client<P, D>) -> Result<S3Response, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut request = S3Request::new("HEAD", bucket, key);
+    request.headers = client.sse_customer_headers.clone();
+    s3request::send(client.s3client.endpoint(), &request)
+}
+
+// Content-Length of a HEAD response.
+fn response_size(response: &S3Response) -> u64 {
+    response.header("Content-Length").and_then(|len| len.parse::<u64>().ok()).unwrap_or(0)
+}
+
+// Returns the total object size from a Content-Range header value such as "bytes 0-99/1234".
+fn content_range_size(content_range: &str) -> Option<u64> {
+    content_range.rsplit('/').next().and_then(|size| size.trim().parse::<u64>().ok())
+}
+
+// Downloads land here until they are complete.
+fn download_temp_path(path: &str) -> String {
+    format!("{}.s3lsio-part", path)
+}
+
+// Records which object version the partial download belongs to.
+fn download_state_path(path: &str) -> String {
+    format!("{}.s3lsio-download", path)
+}
+
+// Folds the timing of one ranged GET into the overall operation so `-t` covers the whole download.
+fn add_operation(total: &mut Operation, part: Operation) {
+    if total.start_time.is_none() {
+        *total = part;
+        return;
+    }
+
+    total.end_time = part.end_time;
+    total.code = part.code;
+    total.success = part.success;
+    total.payload_size += part.payload_size;
+    total.duration = match (total.duration, part.duration) {
+        (Some(total), Some(part)) => Some(total + part),
+        (total, part) => total.or(part),
+    };
+}
+
+/// Downloads the object as GET_CHUNK_SIZE byte ranges spread over ```client.threads``` connections.
+/// Each worker writes its ranges straight into their place in a preallocated temporary file which
+/// is renamed to path once every range has arrived.
+fn get_object_parallel<P, D>(bucket: &str, object: &str, path: &str, operation: Option<&mut Operation>,
+                             client: &Client<P, D>)
+                             -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if object.is_empty() {
+        let error = format!("Object was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let endpoint = client.s3client.endpoint().clone();
+
+    let (size, e_tag) = match head_object_info(bucket, object, client) {
+        Ok((size, e_tag, _)) => (size, e_tag),
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let range_count = (size + GET_CHUNK_SIZE - 1) / GET_CHUNK_SIZE;
+
+    // Nothing to split up
+    if range_count < 2 || client.threads < 2 || client.output.format == OutputFormat::NoneAll {
+        return get_object(bucket, object, path, false, operation, client);
+    }
+
+    let temp_path = download_temp_path(path);
+    let start_time = UTC::now();
+    let now = Instant::now();
+
+    match File::create(&temp_path).and_then(|file| file.set_len(size)) {
+        Ok(_) => {},
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    }
+
+    // Same work sharing as put_multipart_upload.
+    let next_range = Arc::new(Mutex::new(0u64));
+    let (tx, rx) = mpsc::channel();
+    let mut handles = Vec::new();
+    let workers = cmp::min(client.threads as u64, range_count);
+
+    for _ in 0..workers {
+        let endpoint = endpoint.clone();
+        let next_range = next_range.clone();
+        let tx = tx.clone();
+        let bucket = bucket.to_string();
+        let key = object.to_string();
+        let e_tag = e_tag.clone();
+        let temp_path = temp_path.clone();
+        let sse_customer_headers = client.sse_customer_headers.clone();
+
+        handles.push(thread::spawn(move || {
+            let provider = match DefaultCredentialsProviderSync::new(None) {
+                Ok(provider) => provider,
+                Err(e) => {
+                    *next_range.lock().unwrap() = range_count;
+                    // The receiver outlives the workers so a send can not fail.
+                    let _ = tx.send(Err(S3Error::new(format!("{}", e))));
+                    return;
+                },
+            };
+            let s3client = S3Client::new(provider, endpoint);
+
+            loop {
+                let range = {
+                    let mut next = next_range.lock().unwrap();
+                    if *next >= range_count {
+                        break;
+                    }
+                    *next += 1;
+                    *next - 1
+                };
+
+                let offset = range * GET_CHUNK_SIZE;
+                let end = cmp::min(offset + GET_CHUNK_SIZE, size) - 1;
+                let result = get_range_into(&s3client,
+                                            &sse_customer_headers,
+                                            &bucket,
+                                            &key,
+                                            &e_tag,
+                                            offset,
+                                            end,
+                                            &temp_path);
+                let is_err = result.is_err();
+                let _ = tx.send(result);
+                if is_err {
+                    *next_range.lock().unwrap() = range_count;
+                    break;
+                }
+            }
+        }));
+    }
+
+    drop(tx);
+
+    let mut range_error: Option<S3Error> = None;
+    let mut received: u64 = 0;
+
+    for result in rx {
+        match result {
+            Ok(_) => received += 1,
+            Err(e) => {
+                if range_error.is_none() {
+                    range_error = Some(e);
+                }
+            },
+        }
+    }
+
+    // A worker that panicked counts as a failed range.
+    for handle in handles {
+        if handle.join().is_err() && range_error.is_none() {
+            range_error = Some(S3Error::new("A range download thread panicked"));
+        }
+    }
+
+    if range_error.is_none() && received != range_count {
+        range_error = Some(S3Error::new(format!("{} of {} ranges downloaded", received, range_count)));
+    }
+
+    if range_error.is_none() {
+        if let Err(e) = fs::rename(&temp_path, path) {
+            range_error = Some(S3Error::new(format!("{}", e)));
+        }
+    }
+
+    if let Some(op) = operation {
+        op.object = object.to_string();
+        op.method = "GET".to_string();
+        op.success = range_error.is_none();
+        op.code = if range_error.is_none() { 206 } else { 0 };
+        op.payload_size = size;
+        op.duration = Some(now.elapsed());
+        op.start_time = Some(start_time);
+        op.end_time = Some(UTC::now());
+    }
+
+    if let Some(e) = range_error {
+        if let Err(remove_error) = fs::remove_file(&temp_path) {
+            println_color_quiet!(client.is_quiet, client.error.color, "Error removing {}: {}", temp_path, remove_error);
+        }
+        let error = format!("{:#?}", e);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    match client.output.format {
+        OutputFormat::Serialize => {
+            println_color_quiet!(client.is_quiet, client.output.color, "Success");
+        },
+        OutputFormat::Plain => {
+            println_color_quiet!(client.is_quiet, client.output.color, "Success");
+        },
+        OutputFormat::JSON => {
+            println_color_quiet!(client.is_quiet, client.output.color, "Success");
+        },
+        OutputFormat::PrettyJSON => {
+            println_color_quiet!(client.is_quiet, client.output.color, "Success");
+        },
+        OutputFormat::Simple => {
+            println_color_quiet!(client.is_quiet, client.output.color, "Success");
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+// Fetches bytes offset..=end and writes them at the same offset of the file at path. Used by the
+// parallel download workers with their own S3Client.
+fn get_range_into<P, D>(s3client: &S3Client<P, D>,
+                        sse_customer_headers: &[(String, String)],
+                        bucket: &str,
+                        key: &str,
+                        e_tag: &str,
+                        offset: u64,
+                        end: u64,
+                        path: &str)
+                        -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut request = GetObjectRequest::default();
+    request.bucket = bucket.to_string();
+    request.key = key.to_string();
+    request.range = Some(format!("bytes={}-{}", offset, end));
+
+    let output = if sse_customer_headers.is_empty() {
+        try!(s3client.get_object(&request, None))
+    } else {
+        try!(get_object_sse(s3client.endpoint(), &request, sse_customer_headers))
+    };
+
+    if output.e_tag != e_tag {
+        return Err(S3Error::new(format!("Object {} changed while downloading", key)));
+    }
+
+    let body = output.get_body();
+    if body.len() as u64 != end - offset + 1 {
+        return Err(S3Error::new(format!("Range {}-{} returned {} bytes", offset, end, body.len())));
+    }
+
+    let mut file = try!(OpenOptions::new().write(true).open(path).map_err(|e| S3Error::new(format!("{}", e))));
+    try!(file.seek(SeekFrom::Start(offset)).map_err(|e| S3Error::new(format!("{}", e))));
+    try!(file.write_all(body).map_err(|e| S3Error::new(format!("{}", e))));
+
+    Ok(())
+}
+
+fn get_object_range<P, D>(bucket: &str, object: &str, offset: u64, len: u64, path: &str,
+                          operation: Option<&mut Operation>, client: &Client<P, D>)
+                          -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if object.is_empty() {
+        let error = format!("Object was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let mut request = GetObjectRequest::default();
+    request.bucket = bucket.to_string();
+    request.key = object.to_string();
+    request.range = Some(format!("bytes={}-{}", offset, len));
+
+    object_get(&request, path, false, operation, client)
+}
+
+fn get_object_head<P, D>(bucket: &str, object: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if object.is_empty() {
+        let error = format!("Object was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let mut request = HeadObjectRequest::default();
+    request.bucket = bucket.to_string();
+    request.key = object.to_string();
+
+    let result = head_object_output(&request, client);
+
+    match result {
+        Ok(output) => {
+            match client.output.format {
+                OutputFormat::Serialize => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::Plain => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::JSON => {
+                    println_color_quiet!(client.is_quiet,
+                                         client.output.color,
+                                         "{}",
+                                         json::encode(&output).unwrap_or("{}".to_string()));
+                },
+                OutputFormat::PrettyJSON => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&output));
+                },
+                OutputFormat::Simple => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                _ => {},
+            }
+            Ok(())
+        },
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+// HEAD Object sent directly since the SDK does not return the user metadata, send SSE-C headers or
+// parse a Content-Length over 2GB. The metadata keys are returned without the x-amz-meta- prefix.
+fn head_object_output<P, D>(input: &HeadObjectRequest, client: &Client<P, D>) -> Result<ObjectHead, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut request = S3Request::new("HEAD", &input.bucket, &input.key);
+    request.headers = client.sse_customer_headers.clone();
+
+    let response = try!(s3request::send(client.s3client.endpoint(), &request));
+    let header = |name: &str| response.header(name).unwrap_or("").to_string();
+
+    let mut output = ObjectHead::default();
+    output.content_length = try!(header("Content-Length")
+        .parse()
+        .map_err(|e| S3Error::new(format!("Invalid Content-Length {:?}: {}", header("Content-Length"), e))));
+    output.content_type = header("Content-Type");
+    output.e_tag = header("ETag");
+    output.last_modified = header("Last-Modified");
+    output.version_id = header("x-amz-version-id");
+    output.server_side_encryption = header("x-amz-server-side-encryption");
+    output.sse_customer_algorithm = header("x-amz-server-side-encryption-customer-algorithm");
+    output.sse_customer_key_md5 = header("x-amz-server-side-encryption-customer-key-MD5");
+    output.ssekms_key_id = header("x-amz-server-side-encryption-aws-kms-key-id");
+    output.cache_control = header("Cache-Control");
+    output.content_disposition = header("Content-Disposition");
+    output.content_encoding = header("Content-Encoding");
+    output.storage_class = header("x-amz-storage-class");
+    output.expiration = header("x-amz-expiration");
+
+    for (name, value) in &response.headers {
+        let name = name.to_lowercase();
+        if name.starts_with("x-amz-meta-") {
+            output.metadata.insert(name["x-amz-meta-".len()..].to_string(), value.clone());
+        }
+    }
+
+    Ok(output)
+}
+
+fn get_object_acl<P, D>(bucket: &str, object: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if object.is_empty() {
+        let error = format!("Object was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let mut request = GetObjectAclRequest::default();
+    request.bucket = bucket.to_string();
+    request.key = object.to_string();
+
+    match client.s3client.get_object_acl(&request) {
+        Ok(output) => {
+            match client.output.format {
+                OutputFormat::Serialize => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::Plain => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::JSON => {
+                    println_color_quiet!(client.is_quiet,
+                                         client.output.color,
+                                         "{}",
                                          json::encode(&output).unwrap_or("{}".to_string()));
                 },
                 OutputFormat::PrettyJSON => {
@@ -1133,6 +2400,7 @@ fn put_object<P, D>(bucket: &str,
     request.bucket = bucket.to_string();
     request.key = correct_key;
     request.body = Some(&buffer);
+    request.content_type = Some(upload_content_type(object, client));
 
     // Compute hash - Hash is slow
     if client.is_compute_hash {
@@ -1140,7 +2408,14 @@ fn put_object<P, D>(bucket: &str,
         request.content_md5 = Some(hash);
     }
 
-    match client.s3client.put_object(&request, operation) {
+    // The SDK can not send grants, SSE-C keys etc. so requests with upload headers go out directly.
+    let result = if client.upload_headers.is_empty() && client.sse_customer_headers.is_empty() {
+        client.s3client.put_object(&request, operation)
+    } else {
+        put_object_headers(&request, client)
+    };
+
+    match result {
         Ok(output) => {
             match client.output.format {
                 OutputFormat::Serialize => {
@@ -1173,6 +2448,35 @@ fn put_object<P, D>(bucket: &str,
     }
 }
 
+// PUT Object with client.upload_headers. The output is filled in from the response headers.
+fn put_object_headers<P, D>(input: &PutObjectRequest, client: &Client<P, D>) -> Result<PutObjectOutput, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut request = S3Request::new("PUT", &input.bucket, &input.key);
+    request.headers = client.upload_headers.clone();
+    request.headers.extend(client.sse_customer_headers.iter().cloned());
+    if let Some(ref md5) = input.content_md5 {
+        request.headers.push(("Content-MD5".to_string(), md5.clone()));
+    }
+    request.content_type = Some(input.content_type.clone().unwrap_or("binary/octet-stream".to_string()));
+    request.payload = input.body.map(|body| body.to_vec());
+
+    let response = try!(s3request::send(client.s3client.endpoint(), &request));
+    let header = |name: &str| response.header(name).unwrap_or("").to_string();
+
+    let mut output = PutObjectOutput::default();
+    output.e_tag = header("ETag");
+    output.version_id = header("x-amz-version-id");
+    output.expiration = header("x-amz-expiration");
+    output.server_side_encryption = header("x-amz-server-side-encryption");
+    output.ssekms_key_id = header("x-amz-server-side-encryption-aws-kms-key-id");
+    output.sse_customer_algorithm = header("x-amz-server-side-encryption-customer-algorithm");
+    output.sse_customer_key_md5 = header("x-amz-server-side-encryption-customer-key-MD5");
+
+    Ok(output)
+}
+
 fn abort_multipart_upload<P, D>(bucket: &str, object: &str, id: &str, client: &Client<P, D>) -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
@@ -1183,12 +2487,329 @@ fn abort_multipart_upload<P, D>(bucket: &str, object: &str, id: &str, client: &C
         return Err(S3Error::new(error));
     }
 
-    let mut request = MultipartUploadAbortRequest::default();
-    request.bucket = bucket.to_string();
-    request.upload_id = id.to_string();
-    request.key = object.to_string();
+    let mut request = MultipartUploadAbortRequest::default();
+    request.bucket = bucket.to_string();
+    request.upload_id = id.to_string();
+    request.key = object.to_string();
+
+    match client.s3client.multipart_upload_abort(&request) {
+        Ok(output) => {
+            match client.output.format {
+                OutputFormat::Serialize => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::Plain => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                OutputFormat::JSON => {
+                    println_color_quiet!(client.is_quiet,
+                                         client.output.color,
+                                         "{}",
+                                         json::encode(&output).unwrap_or("{}".to_string()));
+                },
+                OutputFormat::PrettyJSON => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&output));
+                },
+                OutputFormat::Simple => {
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
+                },
+                _ => {},
+            }
+        },
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    }
+
+    Ok(())
+}
+
+/// Important - Do not leave incomplete uploads. You will be charged for those parts that have not
+/// been completed. You runs ```s3lsio ls s3://<bucket name> multi``` to find out the uploads
+/// that have not completed and then you an run ```s3lsio abort <upload_id> s3://<bucket_name>/<object_name>```
+/// to abort the upload process.
+///
+/// You can also set a bucket lifecycle rule with ```abort_incomplete_multipart_upload_days``` using
+/// ```s3lsio lifecycle set <file> s3://<bucket_name>``` to automatically abort any uploads that have
+/// not completed after so many days.
+///
+/// The file is split into parts of ```part_size``` bytes which are uploaded concurrently by
+/// ```client.threads``` workers. Each worker reads only the part it is sending so memory use is
+/// bounded by the number of threads times the part size.
+///
+/// Progress is kept in a ```<file>.s3lsio-upload``` state file. If the upload is interrupted then
+/// running the same put again lists the parts S3 already has and only sends the missing ones.
+fn put_multipart_upload<P, D>(bucket: &str,
+                              key: &str,
+                              object: &str,
+                              part_size: u64,
+                              client: &Client<P, D>)
+                              -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let correct_key = if key.is_empty() {
+        let path = Path::new(object);
+        path.file_name().unwrap().to_str().unwrap().to_string()
+    } else {
+        key.to_string()
+    };
+
+    let (file_size, modified) = match fs::metadata(object) {
+        Ok(metadata) => (metadata.len(), file_modified(&metadata)),
+        Err(e) => {
+            let error = format!("Multipart-Upload: Error reading file {}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    // Pick up an earlier upload of the same file if there is one S3 still knows about.
+    let state_path = upload_state_path(object);
+    let mut parts: Vec<(u64, String)> = Vec::new();
+    let state = match load_state::<UploadState>(&state_path) {
+        Some(state) => {
+            if state.bucket == bucket && state.key == correct_key && state.size == file_size &&
+               state.modified == modified {
+                let part_count = if file_size == 0 { 1 } else { (file_size + state.part_size - 1) / state.part_size };
+                let endpoint = client.s3client.endpoint();
+
+                match s3request::list_all_parts(endpoint, bucket, &correct_key, &state.upload_id) {
+                    Ok(uploaded) => {
+                        for part in uploaded {
+                            if part.part_number >= 1 && part.part_number <= part_count &&
+                               part.size == part_length(file_size, state.part_size, part.part_number) {
+                                parts.push((part.part_number, part.e_tag));
+                            }
+                        }
+                        Some(state)
+                    },
+                    Err(_) => None,
+                }
+            } else {
+                None
+            }
+        },
+        None => None,
+    };
+
+    let (upload_id, part_size) = match state {
+        Some(ref state) => (state.upload_id.clone(), state.part_size),
+        None => {
+            // Create multipart
+            let content_type = upload_content_type(object, client);
+            match create_multipart_upload(bucket, &correct_key, Some(&content_type), client) {
+                Ok(upload_id) => (upload_id, multipart_part_size(file_size, part_size)),
+                Err(e) => {
+                    let error = format!("Multipart-Upload: {:#?}", e);
+                    return Err(S3Error::new(error));
+                },
+            }
+        },
+    };
+
+    let part_count = if file_size == 0 { 1 } else { (file_size + part_size - 1) / part_size };
+
+    if state.is_some() && client.output.format != OutputFormat::None &&
+       client.output.format != OutputFormat::NoneAll {
+        println_color_quiet!(client.is_quiet,
+                             client.output.color,
+                             "Resuming upload {}: {} of {} parts already uploaded",
+                             upload_id,
+                             parts.len(),
+                             part_count);
+    }
+
+    let mut state = state.unwrap_or(UploadState {
+        bucket: bucket.to_string(),
+        key: correct_key.clone(),
+        upload_id: upload_id.clone(),
+        size: file_size,
+        modified: modified,
+        part_size: part_size,
+        parts: Vec::new(),
+    });
+    state.parts = parts.iter()
+        .map(|&(part_number, ref e_tag)| UploadStatePart { part_number: part_number, e_tag: e_tag.clone() })
+        .collect();
+    // Without a state file there is nothing to resume from so a failed upload gets aborted instead.
+    let mut is_resumable = save_state(&state_path, &state).is_ok();
+
+    let pending: Vec<u64> = (1..part_count + 1).filter(|n| !parts.iter().any(|&(part, _)| part == *n)).collect();
+    let pending_count = pending.len();
+    let pending = Arc::new(pending);
+
+    // Workers pull the next pending part from a shared index. On failure a worker pushes the index
+    // past the last part so the others stop picking up new work.
+    let next_part = Arc::new(Mutex::new(0usize));
+    let (tx, rx) = mpsc::channel();
+    let mut handles = Vec::new();
+    let workers = cmp::min(client.threads, pending_count);
+
+    for _ in 0..workers {
+        let endpoint = client.s3client.endpoint().clone();
+        let pending = pending.clone();
+        let next_part = next_part.clone();
+        let tx = tx.clone();
+        let bucket = bucket.to_string();
+        let key = correct_key.clone();
+        let upload_id = upload_id.clone();
+        let path = object.to_string();
+        let is_compute_hash = client.is_compute_hash;
+        let sse_customer_headers = client.sse_customer_headers.clone();
+
+        handles.push(thread::spawn(move || {
+            let provider = match DefaultCredentialsProviderSync::new(None) {
+                Ok(provider) => provider,
+                Err(e) => {
+                    *next_part.lock().unwrap() = pending_count;
+                    // The receiver outlives the workers so a send can not fail.
+                    let _ = tx.send(Err(S3Error::new(format!("Multipart-Upload: {}", e))));
+                    return;
+                },
+            };
+            let s3client = S3Client::new(provider, endpoint);
+
+            loop {
+                let part_number = {
+                    let mut next = next_part.lock().unwrap();
+                    if *next >= pending_count {
+                        break;
+                    }
+                    *next += 1;
+                    pending[*next - 1]
+                };
+
+                let result = upload_part(&s3client, &sse_customer_headers, &bucket, &key, &upload_id, &path,
+                                         part_number, part_size, is_compute_hash);
+                let is_err = result.is_err();
+                let _ = tx.send(result.map(|etag| (part_number, etag)));
+                if is_err {
+                    *next_part.lock().unwrap() = pending_count;
+                    break;
+                }
+            }
+        }));
+    }
+
+    // Only the workers hold senders now so the receive loop ends when they are all done.
+    drop(tx);
+
+    let mut part_error: Option<S3Error> = None;
+
+    for result in rx {
+        match result {
+            Ok((part_number, etag)) => {
+                match client.output.format {
+                    OutputFormat::Serialize => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", etag);
+                    },
+                    OutputFormat::Plain => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", etag);
+                    },
+                    OutputFormat::JSON => {
+                        println_color_quiet!(client.is_quiet,
+                                             client.output.color,
+                                             "{}",
+                                             json::encode(&etag).unwrap_or("{}".to_string()));
+                    },
+                    OutputFormat::PrettyJSON => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&etag));
+                    },
+                    OutputFormat::Simple => {
+                        println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", etag);
+                    },
+                    _ => {},
+                }
+                state.parts.push(UploadStatePart { part_number: part_number, e_tag: etag.clone() });
+                is_resumable = is_resumable && save_state(&state_path, &state).is_ok();
+                parts.push((part_number, etag));
+            },
+            Err(e) => {
+                if part_error.is_none() {
+                    part_error = Some(e);
+                }
+            },
+        }
+    }
+
+    // A worker that panicked counts as a failed part.
+    for handle in handles {
+        if handle.join().is_err() && part_error.is_none() {
+            part_error = Some(S3Error::new("Multipart-Upload: a part upload thread panicked"));
+        }
+    }
+
+    if part_error.is_none() && parts.len() as u64 != part_count {
+        part_error = Some(S3Error::new(format!("Multipart-Upload: {} of {} parts uploaded", parts.len(), part_count)));
+    }
+
+    if let Some(e) = part_error {
+        let error = format!("Multipart-Upload Part: {:#?}", e);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+
+        if is_resumable {
+            println_color_quiet!(client.is_quiet,
+                                 client.error.color,
+                                 "Run the same command again to resume upload {} or abort it with: s3lsio abort {} s3://{}/{}",
+                                 upload_id,
+                                 upload_id,
+                                 bucket,
+                                 correct_key);
+        } else {
+            // Do not leave the incomplete upload (and its storage charges) behind.
+            let mut request = MultipartUploadAbortRequest::default();
+            request.bucket = bucket.to_string();
+            request.upload_id = upload_id.to_string();
+            request.key = correct_key.clone();
+            if let Err(e) = client.s3client.multipart_upload_abort(&request) {
+                println_color_quiet!(client.is_quiet,
+                                     client.error.color,
+                                     "Error aborting upload {} of s3://{}/{}: {:#?}",
+                                     upload_id,
+                                     bucket,
+                                     correct_key,
+                                     e);
+            }
+        }
+
+        return Err(S3Error::new(error));
+    }
+    // End of upload
+
+    // Parts complete in any order but must be listed in part number order.
+    parts.sort_by(|a, b| a.0.cmp(&b.0));
+    let parts_list: Vec<String> = parts.into_iter().map(|(_, etag)| etag).collect();
+
+    // Complete multipart
+    let item_list: Vec<u8>;
+
+    let mut request = MultipartUploadCompleteRequest::default();
+    request.bucket = bucket.to_string();
+    request.upload_id = upload_id.to_string();
+    request.key = correct_key;
+
+    // parts_list gets converted to XML and sets the item_list.
+    match multipart_upload_finish_xml(&parts_list) {
+        Ok(parts_in_xml) => item_list = parts_in_xml,
+        Err(e) => {
+            let error = format!("Multipart-Upload XML: {:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    }
+
+    request.multipart_upload = Some(&item_list);
 
-    match client.s3client.multipart_upload_abort(&request) {
+    match client.s3client.multipart_upload_complete(&request) {
         Ok(output) => {
             match client.output.format {
                 OutputFormat::Serialize => {
@@ -1213,72 +2834,130 @@ fn abort_multipart_upload<P, D>(bucket: &str, object: &str, id: &str, client: &C
             }
         },
         Err(e) => {
-            let error = format!("{:#?}", e);
+            let error = format!("Multipart-Upload Complete: {:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
             return Err(S3Error::new(error));
         },
     }
 
+    // The upload is complete so a state file that can not be removed is only reported. It is not
+    // there if it could not be saved.
+    if let Err(e) = fs::remove_file(&state_path) {
+        if e.kind() != io::ErrorKind::NotFound {
+            println_color_quiet!(client.is_quiet, client.error.color, "Error removing {}: {}", state_path, e);
+        }
+    }
+
     Ok(())
 }
 
-/// Important - Do not leave incomplete uploads. You will be charged for those parts that have not
-/// been completed. You runs ```s3lsio ls s3://<bucket name> multi``` to find out the uploads
-/// that have not completed and then you an run ```s3lsio abort <upload_id> s3://<bucket_name>/<object_name>```
-/// to abort the upload process.
-///
-/// You can also apply a bucket policy to automatically abort any uploads that have not completed
-/// after so many days.
-fn put_multipart_upload<P, D>(bucket: &str,
-                              key: &str,
-                              object: &str,
-                              part_size: u64,
-                              client: &Client<P, D>)
-                              -> Result<(), S3Error>
+// Starts a multipart upload. The SDK does not send any headers on create so a direct request is
+// made when there are upload headers or a content type.
+fn create_multipart_upload<P, D>(bucket: &str, key: &str, content_type: Option<&str>, client: &Client<P, D>)
+                                 -> Result<String, S3Error>
     where P: AwsCredentialsProvider,
           D: DispatchSignedRequest,
 {
-    if bucket.is_empty() {
-        let error = format!("Bucket was not specified");
-        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-        return Err(S3Error::new(error));
+    if !client.upload_headers.is_empty() || !client.sse_customer_headers.is_empty() || content_type.is_some() {
+        let mut headers = client.upload_headers.clone();
+        headers.extend(client.sse_customer_headers.iter().cloned());
+        if let Some(content_type) = content_type {
+            headers.retain(|&(ref name, _)| name != "Content-Type");
+            headers.push(("Content-Type".to_string(), content_type.to_string()));
+        }
+        return s3request::create_multipart_upload(client.s3client.endpoint(), bucket, key, &headers);
     }
 
-    let correct_key = if key.is_empty() {
-        let path = Path::new(object);
-        path.file_name().unwrap().to_str().unwrap().to_string()
-    } else {
-        key.to_string()
-    };
-
-    // Create multipart
-    let create_multipart_upload: MultipartUploadCreateOutput;
     let mut request = MultipartUploadCreateRequest::default();
     request.bucket = bucket.to_string();
-    request.key = correct_key.clone();
+    request.key = key.to_string();
 
-    match client.s3client.multipart_upload_create(&request) {
-        Ok(output) => {
-            create_multipart_upload = output;
-        },
-        Err(e) => {
-            let error = format!("Multipart-Upload: {:#?}", e);
-            return Err(S3Error::new(error));
-        },
+    client.s3client.multipart_upload_create(&request).map(|output| output.upload_id)
+}
+
+// Content-Type for an upload of the file at path: --content-type if given, otherwise a guess from the
+// file extension.
+fn upload_content_type<P, D>(path: &str, client: &Client<P, D>) -> String
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    client.upload_headers
+        .iter()
+        .find(|&&(ref name, _)| name == "Content-Type")
+        .map(|&(_, ref value)| value.clone())
+        .unwrap_or(guess_content_type(path).to_string())
+}
+
+// Returns the part size to use for a file. S3 caps an upload at 10,000 parts so the requested size
+// is raised (in whole MB) when the file would need more parts than that.
+fn multipart_part_size(file_size: u64, part_size: u64) -> u64 {
+    let part_size = cmp::max(part_size, PART_SIZE_MIN);
+
+    if file_size / part_size < PART_COUNT_MAX {
+        part_size
+    } else {
+        let mb: u64 = 1048576;
+        let min_size = (file_size + PART_COUNT_MAX - 1) / PART_COUNT_MAX;
+        (min_size + mb - 1) / mb * mb
     }
+}
 
-    let upload_id: &str = &create_multipart_upload.upload_id;
-    let mut parts_list: Vec<String> = Vec::new();
+// Length of the given (1 based) part of a file split into part_size parts.
+fn part_length(file_size: u64, part_size: u64, part_number: u64) -> u64 {
+    cmp::min(part_size, file_size.saturating_sub((part_number - 1) * part_size))
+}
 
-    // NB: To begin with the multipart will be a sequential upload in this thread! Aftwards, it will
-    // be split out to a multiple of threads...
+// Upload state files sit next to the file being uploaded.
+fn upload_state_path(path: &str) -> String {
+    format!("{}.s3lsio-upload", path)
+}
 
-    let file = File::open(object).unwrap();
-    let metadata = file.metadata().unwrap();
+// Reads one of the JSON state files used to resume transfers.
+fn load_state<T: Decodable>(path: &str) -> Option<T> {
+    let mut contents = String::new();
+    match File::open(path).and_then(|mut file| file.read_to_string(&mut contents)) {
+        Ok(_) => json::decode(&contents).ok(),
+        Err(_) => None,
+    }
+}
+
+fn save_state<T: Encodable>(path: &str, state: &T) -> io::Result<()> {
+    let contents = match json::encode(state) {
+        Ok(contents) => contents,
+        Err(e) => return Err(io::Error::new(io::ErrorKind::Other, format!("{}", e))),
+    };
+
+    let mut file = try!(File::create(path));
+    file.write_all(contents.as_bytes())
+}
+
+// Reads a single part from the file and uploads it. Called from the multipart worker threads so it
+// only deals with the worker's own S3Client and returns the ETag of the part.
+fn upload_part<P, D>(s3client: &S3Client<P, D>,
+                     sse_customer_headers: &[(String, String)],
+                     bucket: &str,
+                     key: &str,
+                     upload_id: &str,
+                     path: &str,
+                     part_number: u64,
+                     part_size: u64,
+                     is_compute_hash: bool)
+                     -> Result<String, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return Err(S3Error::new(format!("Multipart-Upload: Error reading file {}", e))),
+    };
+
+    if let Err(e) = file.seek(SeekFrom::Start((part_number - 1) * part_size)) {
+        return Err(S3Error::new(format!("Multipart-Upload: Error reading file {}", e)));
+    }
 
-    let mut part_buffer: Vec<u8> = Vec::with_capacity(metadata.len() as usize);
+    let mut part_buffer: Vec<u8> = Vec::with_capacity(part_size as usize);
 
-    match file.take(metadata.len()).read_to_end(&mut part_buffer) {
+    match file.take(part_size).read_to_end(&mut part_buffer) {
         Ok(_) => {},
         Err(e) => {
             let error = format!("Multipart-Upload: Error reading file {}", e);
@@ -1289,71 +2968,261 @@ fn put_multipart_upload<P, D>(bucket: &str,
     let mut request = MultipartUploadPartRequest::default();
     request.bucket = bucket.to_string();
     request.upload_id = upload_id.to_string();
-    request.key = correct_key.clone();
+    request.key = key.to_string();
 
     request.body = Some(&part_buffer);
-    request.part_number = 1;
+    request.part_number = part_number as i32;
 
     // Compute hash - Hash is slow
-    if client.is_compute_hash {
+    if is_compute_hash {
         let hash = md5::compute(request.body.unwrap()).to_base64(STANDARD);
         request.content_md5 = Some(hash);
     }
 
-    match client.s3client.multipart_upload_part(&request) {
-        Ok(output) => {
-            // Collecting the partid in a list.
-            let new_output = output.clone();
-            parts_list.push(output);
+    // Every part of an SSE-C upload needs the key and the SDK can not send it.
+    let result = if sse_customer_headers.is_empty() {
+        s3client.multipart_upload_part(&request)
+    } else {
+        let mut part_request = S3Request::new("PUT", bucket, key);
+        part_request.subresources.push(("partNumber".to_string(), format!("{}", part_number)));
+        part_request.subresources.push(("uploadId".to_string(), upload_id.to_string()));
+        part_request.headers = sse_customer_headers.to_vec();
+        if let Some(ref md5) = request.content_md5 {
+            part_request.headers.push(("Content-MD5".to_string(), md5.clone()));
+        }
+        part_request.payload = Some(part_buffer.clone());
+        s3request::send(s3client.endpoint(), &part_request)
+            .map(|response| response.header("ETag").unwrap_or("").to_string())
+    };
+
+    match result {
+        Ok(etag) => Ok(etag),
+        Err(e) => Err(S3Error::new(format!("Part {}: {:#?}", part_number, e))),
+    }
+}
+
+/// Server side copy of one object to another (the data never leaves S3). A single CopyObject is
+/// limited to 5GB so larger objects are copied in parts with UploadPartCopy.
+pub fn copy_object<P, D>(src_bucket: &str, src_key: &str, bucket: &str, key: &str, client: &Client<P, D>)
+                         -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() || src_bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if src_key.is_empty() {
+        let error = format!("Object was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let correct_key = copy_key(src_key, key);
+
+    let source = match head_object_response(src_bucket, src_key, client) {
+        Ok(source) => source,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let size = response_size(&source);
+    if size > PUT_SIZE_MAX {
+        return copy_multipart(src_bucket, src_key, &source, size, bucket, &correct_key, client);
+    }
+
+    let mut request = S3Request::new("PUT", bucket, &correct_key);
+    request.headers.push(("x-amz-copy-source".to_string(), copy_source(src_bucket, src_key)));
+    request.headers.extend(client.upload_headers.iter().cloned());
+    // With an SSE-C key both the source and the new object use it.
+    request.headers.extend(client.sse_customer_headers.iter().cloned());
+    request.headers.extend(copy_source_headers(&client.sse_customer_headers));
+    // Without the directive S3 copies the source tags and ignores the new ones.
+    if client.upload_headers.iter().any(|&(ref name, _)| name == "x-amz-tagging") {
+        request.headers.push(("x-amz-tagging-directive".to_string(), "REPLACE".to_string()));
+    }
+    // The same for metadata. With REPLACE only the given metadata and content headers are kept.
+    if client.upload_headers.iter().any(|&(ref name, _)| is_metadata_header(name)) {
+        request.headers.push(("x-amz-metadata-directive".to_string(), "REPLACE".to_string()));
+    }
 
+    match s3request::send(client.s3client.endpoint(), &request).and_then(|response| copy_result(&response.body_str())) {
+        Ok(output) => {
             match client.output.format {
                 OutputFormat::Serialize => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", new_output);
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
                 },
                 OutputFormat::Plain => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", new_output);
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
                 },
                 OutputFormat::JSON => {
                     println_color_quiet!(client.is_quiet,
                                          client.output.color,
                                          "{}",
-                                         json::encode(&new_output).unwrap_or("{}".to_string()));
+                                         json::encode(&output).unwrap_or("{}".to_string()));
                 },
                 OutputFormat::PrettyJSON => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&new_output));
+                    println_color_quiet!(client.is_quiet, client.output.color, "{}", json::as_pretty_json(&output));
                 },
                 OutputFormat::Simple => {
-                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", new_output);
+                    println_color_quiet!(client.is_quiet, client.output.color, "{:#?}", output);
                 },
                 _ => {},
             }
+            Ok(())
+        },
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
         },
+    }
+}
+
+// Copies an object over 5GB as a multipart upload whose parts are ranges of the source. The parts
+// are copied by the worker threads and the upload is aborted if any of them fails.
+fn copy_multipart<P, D>(src_bucket: &str, src_key: &str, source: &S3Response, size: u64, bucket: &str, key: &str,
+                        client: &Client<P, D>)
+                        -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let upload = copy_upload_headers(src_bucket, src_key, source, client)
+        .and_then(|headers| s3request::create_multipart_upload(client.s3client.endpoint(), bucket, key, &headers));
+    let upload_id = match upload {
+        Ok(upload_id) => upload_id,
         Err(e) => {
-            let error = format!("Multipart-Upload Part: {:#?}", e);
+            let error = format!("Multipart-Copy: {:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
             return Err(S3Error::new(error));
         },
+    };
+
+    let part_size = multipart_part_size(size, COPY_PART_SIZE);
+    let part_count = (size + part_size - 1) / part_size;
+
+    let next_part = Arc::new(Mutex::new(0u64));
+    let (tx, rx) = mpsc::channel();
+    let mut handles = Vec::new();
+    let workers = cmp::min(client.threads as u64, part_count);
+
+    for _ in 0..workers {
+        let endpoint = client.s3client.endpoint().clone();
+        let next_part = next_part.clone();
+        let tx = tx.clone();
+        let source = copy_source(src_bucket, src_key);
+        let bucket = bucket.to_string();
+        let key = key.to_string();
+        let upload_id = upload_id.clone();
+        let sse_customer_headers = client.sse_customer_headers.clone();
+
+        handles.push(thread::spawn(move || {
+            loop {
+                let part_number = {
+                    let mut next = next_part.lock().unwrap();
+                    if *next >= part_count {
+                        break;
+                    }
+                    *next += 1;
+                    *next
+                };
+
+                let start = (part_number - 1) * part_size;
+                let end = start + part_length(size, part_size, part_number) - 1;
+
+                let mut request = S3Request::new("PUT", &bucket, &key);
+                request.subresources.push(("partNumber".to_string(), format!("{}", part_number)));
+                request.subresources.push(("uploadId".to_string(), upload_id.clone()));
+                request.headers.push(("x-amz-copy-source".to_string(), source.clone()));
+                request.headers.push(("x-amz-copy-source-range".to_string(), format!("bytes={}-{}", start, end)));
+                request.headers.extend(sse_customer_headers.iter().cloned());
+                request.headers.extend(copy_source_headers(&sse_customer_headers));
+
+                let result = s3request::send(&endpoint, &request)
+                    .and_then(|response| copy_result(&response.body_str()))
+                    .map(|output| (part_number, output.e_tag))
+                    .map_err(|e| S3Error::new(format!("Part {}: {:#?}", part_number, e)));
+                let is_err = result.is_err();
+                // The receiver outlives the workers so a send can not fail.
+                let _ = tx.send(result);
+                if is_err {
+                    *next_part.lock().unwrap() = part_count;
+                    break;
+                }
+            }
+        }));
     }
-    // End of upload
 
-    // Complete multipart
-    let item_list: Vec<u8>;
+    // Only the workers hold senders now so the receive loop ends when they are all done.
+    drop(tx);
 
-    let mut request = MultipartUploadCompleteRequest::default();
-    request.bucket = bucket.to_string();
-    request.upload_id = upload_id.to_string();
-    request.key = correct_key;
+    let mut parts: Vec<(u64, String)> = Vec::new();
+    let mut part_error: Option<S3Error> = None;
 
-    // parts_list gets converted to XML and sets the item_list.
-    match multipart_upload_finish_xml(&parts_list) {
-        Ok(parts_in_xml) => item_list = parts_in_xml,
+    for result in rx {
+        match result {
+            Ok(part) => parts.push(part),
+            Err(e) => {
+                if part_error.is_none() {
+                    part_error = Some(e);
+                }
+            },
+        }
+    }
+
+    // A worker that panicked counts as a failed part.
+    for handle in handles {
+        if handle.join().is_err() && part_error.is_none() {
+            part_error = Some(S3Error::new("Multipart-Copy: a part copy thread panicked"));
+        }
+    }
+
+    if part_error.is_none() && parts.len() as u64 != part_count {
+        part_error = Some(S3Error::new(format!("Multipart-Copy: {} of {} parts copied", parts.len(), part_count)));
+    }
+
+    if let Some(e) = part_error {
+        let error = format!("Multipart-Copy Part: {:#?}", e);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+
+        let mut request = MultipartUploadAbortRequest::default();
+        request.bucket = bucket.to_string();
+        request.upload_id = upload_id.to_string();
+        request.key = key.to_string();
+        if let Err(e) = client.s3client.multipart_upload_abort(&request) {
+            println_color_quiet!(client.is_quiet,
+                                 client.error.color,
+                                 "Error aborting upload {} of s3://{}/{}: {:#?}",
+                                 upload_id,
+                                 bucket,
+                                 key,
+                                 e);
+        }
+
+        return Err(S3Error::new(error));
+    }
+
+    parts.sort_by(|a, b| a.0.cmp(&b.0));
+    let parts_list: Vec<String> = parts.into_iter().map(|(_, etag)| etag).collect();
+
+    let item_list = match multipart_upload_finish_xml(&parts_list) {
+        Ok(parts_in_xml) => parts_in_xml,
         Err(e) => {
-            let error = format!("Multipart-Upload XML: {:#?}", e);
+            let error = format!("Multipart-Copy XML: {:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
             return Err(S3Error::new(error));
         },
-    }
+    };
 
+    let mut request = MultipartUploadCompleteRequest::default();
+    request.bucket = bucket.to_string();
+    request.upload_id = upload_id.to_string();
+    request.key = key.to_string();
     request.multipart_upload = Some(&item_list);
 
     match client.s3client.multipart_upload_complete(&request) {
@@ -1379,18 +3248,96 @@ fn put_multipart_upload<P, D>(bucket: &str,
                 },
                 _ => {},
             }
+            Ok(())
         },
         Err(e) => {
-            let error = format!("Multipart-Upload Complete: {:#?}", e);
+            let error = format!("Multipart-Copy Complete: {:#?}", e);
             println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
-            return Err(S3Error::new(error));
+            Err(S3Error::new(error))
         },
     }
+}
 
-    Ok(())
+// Headers for the multipart upload of a copy. CopyObject keeps the content headers, metadata, tags
+// and encryption of the source so they are taken from its HEAD response here too. As with the
+// REPLACE directives of a single copy, any metadata or tag option replaces those of the source.
+fn copy_upload_headers<P, D>(src_bucket: &str, src_key: &str, source: &S3Response, client: &Client<P, D>)
+                             -> Result<Vec<(String, String)>, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut headers = client.upload_headers.clone();
+    let is_given = |name: &str| client.upload_headers.iter().any(|&(ref header, _)| header == name);
+
+    if !client.upload_headers.iter().any(|&(ref name, _)| is_metadata_header(name)) {
+        for name in &["Content-Type", "Cache-Control", "Content-Disposition", "Content-Encoding"] {
+            if let Some(value) = source.header(name) {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+        for (name, value) in &source.headers {
+            let name = name.to_lowercase();
+            if name.starts_with("x-amz-meta-") {
+                headers.push((name, value.clone()));
+            }
+        }
+    }
+
+    // HEAD only returns the number of tags.
+    let tag_count = source.header("x-amz-tagging-count").and_then(|count| count.parse::<u64>().ok()).unwrap_or(0);
+    if !is_given("x-amz-tagging") && tag_count > 0 {
+        let tags = try!(object_tags(client.s3client.endpoint(), src_bucket, src_key));
+        headers.push(tagging_header(tags.iter()));
+    }
+
+    // SSE-C objects are copied with the customer key instead.
+    if !is_given("x-amz-server-side-encryption") && client.sse_customer_headers.is_empty() {
+        if let Some(encryption) = source.header("x-amz-server-side-encryption") {
+            headers.push(("x-amz-server-side-encryption".to_string(), encryption.to_string()));
+            if let Some(key_id) = source.header("x-amz-server-side-encryption-aws-kms-key-id") {
+                headers.push(("x-amz-server-side-encryption-aws-kms-key-id".to_string(), key_id.to_string()));
+            }
+        }
+    }
+
+    headers.extend(client.sse_customer_headers.iter().cloned());
+
+    Ok(headers)
+}
+
+// Copying into a bucket (or prefix ending in '/') keeps the source object name.
+fn copy_key(src_key: &str, key: &str) -> String {
+    if key.is_empty() || key.ends_with('/') {
+        format!("{}{}", key, src_key.rsplit('/').next().unwrap_or(src_key))
+    } else {
+        key.to_string()
+    }
+}
+
+// Value of the x-amz-copy-source header: /bucket/key with the key URL encoded.
+fn copy_source(bucket: &str, key: &str) -> String {
+    let segments: Vec<String> = key.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).collect::<String>())
+        .collect();
+
+    format!("/{}/{}", bucket, segments.join("/"))
+}
+
+// Copy requests can fail after S3 has already sent back a 200 so the body has to be checked too.
+fn copy_result(body: &str) -> Result<CopyObjectResult, S3Error> {
+    if body.contains("<Error>") {
+        return Err(S3Error::new(format!("{}: {}",
+                                        s3request::xml_value(body, "Code").unwrap_or(String::new()),
+                                        s3request::xml_value(body, "Message").unwrap_or(String::new()))));
+    }
+
+    let mut result = CopyObjectResult::default();
+    result.e_tag = s3request::xml_value(body, "ETag").unwrap_or(String::new());
+    result.last_modified = s3request::xml_value(body, "LastModified").unwrap_or(String::new());
+    Ok(result)
 }
 
-fn delete_object<P, D>(bucket: &str, object: &str, version: &str, operation: Option<&mut Operation>,
+pub fn delete_object<P, D>(bucket: &str, object: &str, version: &str, operation: Option<&mut Operation>,
                        client: &Client<P, D>)
                        -> Result<(), S3Error>
     where P: AwsCredentialsProvider,
@@ -1448,3 +3395,98 @@ fn delete_object<P, D>(bucket: &str, object: &str, version: &str, operation: Opt
 
     Ok(())
 }
+
+// Deletes (key, version id) pairs with Multi-Object Delete requests of up to 1000 keys. Keys S3
+// could not delete are reported one by one and any failure fails the whole call.
+fn delete_batches<P, D>(bucket: &str, keys: &[(String, String)], client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut failed: usize = 0;
+
+    for batch in keys.chunks(DELETE_BATCH_MAX) {
+        match s3request::delete_objects(client.s3client.endpoint(), bucket, batch) {
+            Ok(errors) => {
+                for error in &errors {
+                    println_color_quiet!(client.is_quiet,
+                                         client.error.color,
+                                         "Error deleting s3://{}/{}{}: {} {}",
+                                         bucket,
+                                         error.key,
+                                         if error.version_id.is_empty() {
+                                             String::new()
+                                         } else {
+                                             format!(" (version {})", error.version_id)
+                                         },
+                                         error.code,
+                                         error.message);
+                }
+                failed += errors.len();
+            },
+            Err(e) => {
+                println_color_quiet!(client.is_quiet, client.error.color, "{:#?}", e);
+                failed += batch.len();
+            },
+        }
+    }
+
+    if failed > 0 {
+        let error = format!("{} of {} objects failed to delete", failed, keys.len());
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    Ok(())
+}
+
+/// Deletes every object under prefix (the whole bucket if prefix is empty) that passes the filter
+/// with Multi-Object Delete requests of up to 1000 keys. Keys S3 could not delete are reported
+/// one by one. With is_dry_run the keys are only listed.
+fn delete_objects_recursive<P, D>(bucket: &str, prefix: &str, filter: &KeyFilter, is_dry_run: bool,
+                                  client: &Client<P, D>)
+                                  -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if bucket.is_empty() {
+        let error = format!("Bucket was not specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let prefix = prefix_dir(prefix);
+    let objects = match s3request::list_all_objects(client.s3client.endpoint(), bucket, &prefix) {
+        Ok(objects) => objects,
+        Err(e) => {
+            let error = format!("{:#?}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            return Err(S3Error::new(error));
+        },
+    };
+
+    let keys: Vec<(String, String)> = objects.into_iter()
+        .filter(|object| filter.is_match(&object.key[prefix.len()..]))
+        .map(|object| (object.key, String::new()))
+        .collect();
+
+    if is_dry_run {
+        for &(ref key, _) in &keys {
+            println_color_quiet!(client.is_quiet, client.output.color, "(dry-run) delete: s3://{}/{}", bucket, key);
+        }
+        return Ok(());
+    }
+
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    try!(confirm_action(&format!("delete {} objects under s3://{}/{}", keys.len(), bucket, prefix), client));
+
+    try!(delete_batches(bucket, &keys, client));
+
+    if (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet, client.output.color, "{} objects deleted", keys.len());
+    }
+
+    Ok(())
+}
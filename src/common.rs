@@ -15,7 +15,22 @@
 #![allow(unused_mut)]
 #![allow(unused_assignments)]
 
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use md5;
+use libc;
+use lsio::prompts;
+use rustc_serialize::hex::ToHex;
+
 use clap::ArgMatches;
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+
+use Client;
 
 /// Finds the bucket, object and last values based on the level of the ArgMatches
 ///
@@ -85,3 +100,117 @@ pub fn get_bucket(cli_bucket: String) -> Option<String> {
 
     Some(bucket.to_string())
 }
+
+/// Returns every file under dir (recursively). Symlinked directories are not followed.
+///
+pub fn walk_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut dirs: Vec<PathBuf> = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in try!(fs::read_dir(&dir)) {
+            let path = try!(entry).path();
+            let metadata = try!(fs::symlink_metadata(&path));
+
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Turns a path relative to the root of a walk into an object key suffix ('/' separated).
+///
+pub fn relative_key(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let components: Vec<String> = relative.components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    components.join("/")
+}
+
+/// The reverse of ```relative_key```: where an object key suffix goes under root. Leading '/'s are
+/// dropped and None is returned for a key that would end up outside of root ('..', absolute or
+/// drive prefixed paths) since keys come from the bucket and can not be trusted.
+///
+pub fn local_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative.trim_left_matches('/'));
+    let is_safe = relative.components().all(|component| match component {
+        Component::Normal(_) | Component::CurDir => true,
+        _ => false,
+    });
+
+    if !is_safe || relative.components().next().is_none() {
+        return None;
+    }
+
+    let path = root.join(relative);
+    if path.starts_with(root) { Some(path) } else { None }
+}
+
+/// Modified time of a file in seconds since the epoch (0 if the platform does not have it).
+///
+pub fn file_modified(metadata: &fs::Metadata) -> u64 {
+    metadata.modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Hex MD5 of a file which is what S3 returns as the ETag of an object uploaded in a single PUT.
+///
+pub fn file_md5(path: &Path) -> io::Result<String> {
+    let mut file = try!(fs::File::open(path));
+    let mut context = md5::Context::new();
+    try!(io::copy(&mut file, &mut context));
+    Ok(context.compute().to_hex())
+}
+
+/// Asks before a destructive action. ```--yes``` skips the question. Without ```--yes``` a
+/// non-interactive run (stdin is not a terminal) is refused rather than left waiting for an answer.
+/// Returns an error if the action should not go ahead.
+///
+pub fn confirm_action<P, D>(action: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if client.is_yes {
+        return Ok(());
+    }
+
+    let error = if !is_interactive() {
+        format!("Refusing to {} without --yes when not running interactively", action)
+    } else if prompts::confirm(&format!("{}? [y/N]", capitalize(action)), true, client.output.color) {
+        return Ok(());
+    } else {
+        format!("Cancelled: {}", action)
+    };
+
+    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+    Err(S3Error::new(error))
+}
+
+#[cfg(unix)]
+fn is_interactive() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO) != 0 }
+}
+
+#[cfg(not(unix))]
+fn is_interactive() -> bool {
+    true
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2016 LambdaStack All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client side ```--include```/```--exclude``` filtering of keys (or local paths) returned by a
+//! listing. Patterns are globs where ```*``` matches any run of characters (including '/') and
+//! ```?``` matches a single character. With ```--regex``` the patterns are regular expressions.
+//!
+//! A key is kept when it matches at least one include (or no includes were given) and none of the
+//! excludes. Patterns are matched against the key relative to the prefix being listed.
+
+use clap::ArgMatches;
+use regex::{self, Regex};
+
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+
+use Client;
+
+/// Compiled include/exclude patterns.
+///
+#[derive(Debug, Default, Clone)]
+pub struct KeyFilter {
+    includes: Vec<Regex>,
+    excludes: Vec<Regex>,
+}
+
+impl KeyFilter {
+    pub fn new(includes: &[&str], excludes: &[&str], is_regex: bool) -> Result<KeyFilter, String> {
+        Ok(KeyFilter {
+            includes: try!(patterns(includes, is_regex)),
+            excludes: try!(patterns(excludes, is_regex)),
+        })
+    }
+
+    pub fn is_match(&self, key: &str) -> bool {
+        (self.includes.is_empty() || self.includes.iter().any(|include| include.is_match(key))) &&
+        !self.excludes.iter().any(|exclude| exclude.is_match(key))
+    }
+}
+
+/// Builds the filter from the ```--include```, ```--exclude``` and ```--regex``` options of a
+/// subcommand. An invalid pattern is reported and returned as an error.
+pub fn key_filter<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<KeyFilter, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let includes: Vec<&str> = matches.values_of("include").map(|values| values.collect()).unwrap_or(Vec::new());
+    let excludes: Vec<&str> = matches.values_of("exclude").map(|values| values.collect()).unwrap_or(Vec::new());
+
+    match KeyFilter::new(&includes, &excludes, matches.is_present("regex")) {
+        Ok(filter) => Ok(filter),
+        Err(e) => {
+            let error = format!("Invalid filter pattern: {}", e);
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+            Err(S3Error::new(error))
+        },
+    }
+}
+
+fn patterns(patterns: &[&str], is_regex: bool) -> Result<Vec<Regex>, String> {
+    let mut compiled = Vec::new();
+
+    for pattern in patterns {
+        let expression = if is_regex { pattern.to_string() } else { glob_regex(pattern) };
+        match Regex::new(&expression) {
+            Ok(regex) => compiled.push(regex),
+            Err(e) => return Err(format!("{}: {}", pattern, e)),
+        }
+    }
+
+    Ok(compiled)
+}
+
+// Anchored regular expression for a glob.
+fn glob_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+
+    for c in glob.chars() {
+        match c {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            _ => expression.push_str(&regex::quote(&c.to_string())),
+        }
+    }
+
+    expression.push('$');
+    expression
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_star_matches_across_slashes() {
+        let filter = KeyFilter::new(&["*.log"], &[], false).unwrap();
+        assert!(filter.is_match("app.log"));
+        assert!(filter.is_match("2016/10/app.log"));
+        assert!(!filter.is_match("app.log.gz"));
+    }
+
+    #[test]
+    fn glob_question_mark_matches_one_character() {
+        let filter = KeyFilter::new(&["part-?.csv"], &[], false).unwrap();
+        assert!(filter.is_match("part-1.csv"));
+        assert!(filter.is_match("part-/.csv"));
+        assert!(!filter.is_match("part-.csv"));
+        assert!(!filter.is_match("part-12.csv"));
+    }
+
+    #[test]
+    fn glob_escapes_regex_metacharacters() {
+        assert_eq!(glob_regex("a.b+(c)[d]{e}^$|\\"), "^a\\.b\\+\\(c\\)\\[d\\]\\{e\\}\\^\\$\\|\\\\$");
+
+        let filter = KeyFilter::new(&["file.(1)+[a].txt"], &[], false).unwrap();
+        assert!(filter.is_match("file.(1)+[a].txt"));
+        assert!(!filter.is_match("fileX(1)+[a].txt"));
+        assert!(!filter.is_match("file.11a.txt"));
+    }
+
+    #[test]
+    fn glob_is_anchored() {
+        let filter = KeyFilter::new(&["logs/*"], &[], false).unwrap();
+        assert!(filter.is_match("logs/a"));
+        assert!(!filter.is_match("old/logs/a"));
+    }
+
+    #[test]
+    fn no_patterns_match_everything() {
+        let filter = KeyFilter::default();
+        assert!(filter.is_match(""));
+        assert!(filter.is_match("any/key"));
+    }
+
+    #[test]
+    fn exclude_takes_precedence_over_include() {
+        let filter = KeyFilter::new(&["*.txt", "*.csv"], &["tmp/*"], false).unwrap();
+        assert!(filter.is_match("a.txt"));
+        assert!(filter.is_match("data/b.csv"));
+        assert!(!filter.is_match("tmp/a.txt"));
+        assert!(!filter.is_match("c.json"));
+
+        let filter = KeyFilter::new(&[], &["*.tmp"], false).unwrap();
+        assert!(filter.is_match("a.txt"));
+        assert!(!filter.is_match("a.tmp"));
+    }
+
+    #[test]
+    fn regex_patterns_are_used_as_given() {
+        let filter = KeyFilter::new(&[r"^\d{4}/.*\.log$"], &["debug"], true).unwrap();
+        assert!(filter.is_match("2016/app.log"));
+        assert!(!filter.is_match("logs/2016/app.log"));
+        assert!(!filter.is_match("2016/debug.log"));
+
+        // Unlike globs a regex is not anchored unless it says so.
+        let filter = KeyFilter::new(&["b"], &[], true).unwrap();
+        assert!(filter.is_match("abc"));
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        assert!(KeyFilter::new(&["("], &[], true).is_err());
+        assert!(KeyFilter::new(&["("], &[], false).is_ok());
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2016 LambdaStack All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Request headers built from command line options. ```upload_headers``` collects the options of
+//! ```put```, ```cp``` and ```mv``` that apply when an object is created. They are kept on the
+//! ```Client``` so single PUTs, multipart uploads and copies all send the same headers.
+//!
+//! SSE-C (customer key) headers are kept separately since they are also needed to read the object
+//! back (get, range and head) and on every part of a multipart upload.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use clap::ArgMatches;
+use url::form_urlencoded;
+use md5;
+use rustc_serialize::base64::{STANDARD, ToBase64};
+
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+
+use Client;
+
+/// Canned ACLs S3 accepts in ```x-amz-acl```.
+pub const CANNED_ACLS: [&'static str; 8] = ["private",
+                                            "public-read",
+                                            "public-read-write",
+                                            "authenticated-read",
+                                            "aws-exec-read",
+                                            "bucket-owner-read",
+                                            "bucket-owner-full-control",
+                                            "log-delivery-write"];
+
+/// ```--grant-*``` options and the header each one is sent as.
+pub const GRANTS: [(&'static str, &'static str); 5] = [("grant-read", "x-amz-grant-read"),
+                                                       ("grant-write", "x-amz-grant-write"),
+                                                       ("grant-read-acp", "x-amz-grant-read-acp"),
+                                                       ("grant-write-acp", "x-amz-grant-write-acp"),
+                                                       ("grant-full-control", "x-amz-grant-full-control")];
+
+/// ```--content-*``` style options and the header each one is sent as.
+const CONTENT_HEADERS: [(&'static str, &'static str); 4] = [("content-type", "Content-Type"),
+                                                            ("cache-control", "Cache-Control"),
+                                                            ("content-disposition", "Content-Disposition"),
+                                                            ("content-encoding", "Content-Encoding")];
+
+/// Builds the ```x-amz-acl``` header for a canned ACL (if not empty) and the ```x-amz-grant-*```
+/// headers for any ```--grant-*``` options.
+pub fn acl_headers(canned: &str, matches: &ArgMatches) -> Result<Vec<(String, String)>, String> {
+    let mut headers = Vec::new();
+
+    if !canned.is_empty() {
+        let canned = canned.to_lowercase();
+        // public-rw and public-readwrite were the names `acl set` has always accepted.
+        let acl = match canned.as_ref() {
+            "public-rw" | "public-readwrite" => "public-read-write",
+            acl => acl,
+        };
+        if !CANNED_ACLS.contains(&acl) {
+            return Err(format!("Invalid acl {}: must be one of {}", canned, CANNED_ACLS.join(", ")));
+        }
+        headers.push(("x-amz-acl".to_string(), acl.to_string()));
+    }
+
+    for &(option, header) in GRANTS.iter() {
+        if let Some(values) = matches.values_of(option) {
+            let mut grantees = Vec::new();
+            for value in values {
+                grantees.push(try!(grantee(value)));
+            }
+            headers.push((header.to_string(), grantees.join(", ")));
+        }
+    }
+
+    Ok(headers)
+}
+
+/// Headers for a new object from the ```put```, ```cp``` or ```mv``` options. Invalid options are
+/// reported and returned as an error.
+pub fn upload_headers<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<Vec<(String, String)>, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match object_headers(matches) {
+        Ok(headers) => Ok(headers),
+        Err(e) => {
+            println_color_quiet!(client.is_quiet, client.error.color, "{}", e);
+            Err(S3Error::new(e))
+        },
+    }
+}
+
+/// True for the headers that are stored as object metadata (a copy only takes them with
+/// ```x-amz-metadata-directive: REPLACE```).
+pub fn is_metadata_header(name: &str) -> bool {
+    CONTENT_HEADERS.iter().any(|&(_, header)| header == name) || name.starts_with("x-amz-meta-")
+}
+
+/// The ```x-amz-tagging``` header (URL encoded key=value pairs) for the tags of a new object.
+pub fn tagging_header<'a, I>(tags: I) -> (String, String)
+    where I: Iterator<Item = (&'a String, &'a String)>,
+{
+    let mut tagging = form_urlencoded::Serializer::new(String::new());
+    for (key, value) in tags {
+        tagging.append_pair(key, value);
+    }
+    ("x-amz-tagging".to_string(), tagging.finish())
+}
+
+/// Content-Type for a file based on its extension. binary/octet-stream (the S3 default) when it is
+/// not known.
+pub fn guess_content_type(path: &str) -> &'static str {
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or("");
+
+    match extension.to_lowercase().as_ref() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "wasm" => "application/wasm",
+        _ => "binary/octet-stream",
+    }
+}
+
+// Everything put, cp and mv can set on the new object.
+fn object_headers(matches: &ArgMatches) -> Result<Vec<(String, String)>, String> {
+    let mut headers = try!(acl_headers(matches.value_of("acl").unwrap_or(""), matches));
+
+    if let Some(values) = matches.values_of("tag") {
+        let mut tags = Vec::new();
+        for value in values {
+            tags.push(try!(key_value(value)));
+        }
+        headers.push(tagging_header(tags.iter().map(|&(ref key, ref value)| (key, value))));
+    }
+
+    headers.extend(try!(sse_headers(matches)));
+
+    for &(option, header) in CONTENT_HEADERS.iter() {
+        if let Some(value) = matches.value_of(option) {
+            headers.push((header.to_string(), value.to_string()));
+        }
+    }
+
+    if let Some(storage_class) = matches.value_of("storage-class") {
+        headers.push(("x-amz-storage-class".to_string(), storage_class.to_uppercase()));
+    }
+
+    if let Some(values) = matches.values_of("metadata") {
+        for value in values {
+            let (key, value) = try!(key_value(value));
+            headers.push((format!("x-amz-meta-{}", key.to_lowercase()), value));
+        }
+    }
+
+    Ok(headers)
+}
+
+/// SSE-C headers from ```--sse-c-key-file``` (empty if the option was not given). Invalid options
+/// are reported and returned as an error.
+pub fn sse_customer_headers<P, D>(matches: &ArgMatches, client: &Client<P, D>)
+                                  -> Result<Vec<(String, String)>, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let path = match matches.value_of("sse-c-key-file") {
+        Some(path) => path,
+        None => return Ok(Vec::new()),
+    };
+
+    // The key file holds the raw 256 bit key.
+    let mut key = Vec::new();
+    let read = File::open(path).and_then(|mut file| file.read_to_end(&mut key));
+    let error = match read {
+        Err(e) => Some(format!("Error reading {}: {}", path, e)),
+        Ok(_) if key.len() != 32 => {
+            Some(format!("{} must hold a 32 byte (256 bit) key but has {} bytes", path, key.len()))
+        },
+        Ok(_) => None,
+    };
+
+    if let Some(error) = error {
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    Ok(vec![("x-amz-server-side-encryption-customer-algorithm".to_string(), "AES256".to_string()),
+            ("x-amz-server-side-encryption-customer-key".to_string(), key.to_base64(STANDARD)),
+            ("x-amz-server-side-encryption-customer-key-MD5".to_string(), md5::compute(&key).to_base64(STANDARD))])
+}
+
+/// The SSE-C headers that describe the source of a copy.
+pub fn copy_source_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    headers.iter()
+        .map(|&(ref name, ref value)| {
+            (name.replace("x-amz-server-side", "x-amz-copy-source-server-side"), value.clone())
+        })
+        .collect()
+}
+
+// --sse AES256|aws:kms and --sse-kms-key-id (which implies aws:kms).
+fn sse_headers(matches: &ArgMatches) -> Result<Vec<(String, String)>, String> {
+    let key_id = matches.value_of("sse-kms-key-id");
+    let sse = match (matches.value_of("sse"), key_id) {
+        (None, None) => return Ok(Vec::new()),
+        (None, Some(_)) => "aws:kms".to_string(),
+        (Some(sse), _) if sse.to_lowercase() == "aes256" => "AES256".to_string(),
+        (Some(sse), _) if sse.to_lowercase() == "aws:kms" => "aws:kms".to_string(),
+        (Some(sse), _) => return Err(format!("Invalid sse {}: must be AES256 or aws:kms", sse)),
+    };
+
+    if matches.is_present("sse-c-key-file") {
+        return Err("--sse and --sse-kms-key-id can not be used with --sse-c-key-file".to_string());
+    }
+    if sse == "AES256" && key_id.is_some() {
+        return Err("--sse-kms-key-id needs --sse aws:kms".to_string());
+    }
+
+    let mut headers = vec![("x-amz-server-side-encryption".to_string(), sse)];
+    if let Some(key_id) = key_id {
+        headers.push(("x-amz-server-side-encryption-aws-kms-key-id".to_string(), key_id.to_string()));
+    }
+
+    Ok(headers)
+}
+
+/// Splits a ```key=value``` tag or metadata pair. The value may be empty.
+pub fn key_value(pair: &str) -> Result<(String, String), String> {
+    match pair.find('=') {
+        Some(index) if index > 0 => Ok((pair[..index].to_string(), pair[index + 1..].to_string())),
+        _ => Err(format!("Invalid {}: must be key=value", pair)),
+    }
+}
+
+// id=<canonical id>, email=<address> or uri=<group uri> in the form S3 expects in a grant header.
+fn grantee(value: &str) -> Result<String, String> {
+    let (kind, grantee) = match value.find('=') {
+        Some(index) => (&value[..index], &value[index + 1..]),
+        None => return Err(format!("Invalid grantee {}: must be id=, email= or uri=", value)),
+    };
+
+    let kind = match kind.to_lowercase().as_ref() {
+        "id" => "id",
+        "email" | "emailaddress" => "emailAddress",
+        "uri" => "uri",
+        _ => return Err(format!("Invalid grantee {}: must be id=, email= or uri=", value)),
+    };
+
+    Ok(format!("{}=\"{}\"", kind, grantee.trim_matches('"')))
+}
@@ -55,6 +55,9 @@ extern crate md5;
 extern crate time;
 extern crate chrono;
 extern crate rand;
+extern crate regex;
+extern crate libc;
+extern crate openssl;
 
 use std::io;
 use std::env;
@@ -81,6 +84,12 @@ mod config;
 mod commands;
 mod bench;
 mod ceph_admin;
+mod s3request;
+mod filter;
+mod sync;
+mod bucket_config;
+mod headers;
+mod presign;
 
 static DEFAULT_USER_AGENT: &'static str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
@@ -107,16 +116,25 @@ pub enum Commands {
     abort,
     acl,
     admin,  // Admin for Ceph RGW only
+    cors,
     cp,
+    encryption,
     get,
     head,
+    lifecycle,
     mb,
+    mv,
+    policy,
+    presign,
     put,
     range,
     rb,
     rm,
     ls,
+    sync,
+    tag,
     ver,
+    website,
 }
 
 // Error and Output can't have derive(debug) because term does not have some of it's structs
@@ -166,6 +184,12 @@ pub struct Client<'a, P: 'a, D: 'a>
     pub is_time: bool,
     pub is_bench: bool,
     pub is_compute_hash: bool,
+    pub is_yes: bool,
+    pub threads: usize,
+    // Headers sent when put/cp/mv create an object (see headers::upload_headers).
+    pub upload_headers: Vec<(String, String)>,
+    // SSE-C key headers for the objects read or written (see headers::sse_customer_headers).
+    pub sse_customer_headers: Vec<(String, String)>,
 }
 
 fn main() {
@@ -174,6 +198,7 @@ fn main() {
     let mut is_time: bool = false;
     let mut is_bench: bool = false;
     let mut is_compute_hash: bool = false;
+    let mut is_yes: bool = false;
     let mut is_keep_alive: bool = false;
     let mut is_bucket_virtual: bool = true;
 
@@ -214,6 +239,11 @@ fn main() {
         is_quiet = true;
     }
 
+    // If the -y or --yes flag was passed then destructive commands do not ask first
+    if matches.is_present("yes") {
+        is_yes = true;
+    }
+
     // If the -t or --time flag was passed then track operation time
     if matches.is_present("time") {
         is_time = true;
@@ -323,6 +353,10 @@ fn main() {
 
     let mut s3client = S3Client::new(provider, endpoint);
 
+    // Number of worker threads for transfers that can be split up. Never less than one.
+    let threads: usize = matches.value_of("threads").unwrap_or("4").parse().unwrap_or(4);
+    let threads = if threads == 0 { 1 } else { threads };
+
     let output = Output{format: output_format, color: output_color};
     let bench_output = BenchOutput{format: output_bench_format, color: output_color};
 
@@ -338,6 +372,10 @@ fn main() {
         is_time: is_time,
         is_bench: is_bench,
         is_compute_hash: is_compute_hash,
+        is_yes: is_yes,
+        threads: threads,
+        upload_headers: Vec::new(),
+        sse_customer_headers: Vec::new(),
     };
 
     // Check which subcomamnd the user wants to run...
@@ -353,18 +391,27 @@ fn main() {
             // with new TCP connections.
             benchmarking(sub_matches, bench, ep_str, is_bucket_virtual, is_keep_alive, bench_output, &client)
         },
+        ("encryption", Some(sub_matches)) => commands::commands(sub_matches, Commands::encryption, &mut client),
         ("get", Some(sub_matches)) => commands::commands(sub_matches, Commands::get, &mut client),
+        ("cors", Some(sub_matches)) => commands::commands(sub_matches, Commands::cors, &mut client),
         ("cp", Some(sub_matches)) => commands::commands(sub_matches, Commands::cp, &mut client),
         ("head", Some(sub_matches)) => commands::commands(sub_matches, Commands::head, &mut client),
+        ("lifecycle", Some(sub_matches)) => commands::commands(sub_matches, Commands::lifecycle, &mut client),
         ("ls", Some(sub_matches)) => commands::commands(sub_matches, Commands::ls, &mut client),
         ("mb", Some(sub_matches)) => commands::commands(sub_matches, Commands::mb, &mut client),
+        ("mv", Some(sub_matches)) => commands::commands(sub_matches, Commands::mv, &mut client),
+        ("policy", Some(sub_matches)) => commands::commands(sub_matches, Commands::policy, &mut client),
+        ("presign", Some(sub_matches)) => commands::commands(sub_matches, Commands::presign, &mut client),
         ("put", Some(sub_matches)) => commands::commands(sub_matches, Commands::put, &mut client),
         ("range", Some(sub_matches)) => commands::commands(sub_matches, Commands::range, &mut client),
         ("rb", Some(sub_matches)) => commands::commands(sub_matches, Commands::rb, &mut client),
         ("rm", Some(sub_matches)) => commands::commands(sub_matches, Commands::rm, &mut client),
+        ("sync", Some(sub_matches)) => commands::commands(sub_matches, Commands::sync, &mut client),
         //("setacl", Some(sub_matches)) => commands::commands(sub_matches, Commands::setacl, &mut client),
         //("setver", Some(sub_matches)) => commands::commands(sub_matches, Commands::setver, &mut client),
+        ("tag", Some(sub_matches)) => commands::commands(sub_matches, Commands::tag, &mut client),
         ("ver", Some(sub_matches)) => commands::commands(sub_matches, Commands::ver, &mut client),
+        ("website", Some(sub_matches)) => commands::commands(sub_matches, Commands::website, &mut client),
         (e, _) => {
             let error = format!("Command {} not recognized", e);
             println_color_quiet!(client.is_quiet, term::color::RED, "{}", error);
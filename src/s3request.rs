@@ -0,0 +1,576 @@
+// Copyright 2016 LambdaStack All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signed requests for the S3 operations (or parts of operations) that ```S3Client``` does not
+//! handle. The requests are built and signed with the same ```SignedRequest``` the SDK uses so
+//! V2/V4 signatures, virtual host buckets and proxies behave like every other s3lsio command.
+
+use std::collections::HashMap;
+use std::sync::{Once, ONCE_INIT};
+
+use md5;
+use rustc_serialize::base64::{STANDARD, ToBase64};
+
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::errors::aws::AWSError;
+use aws_sdk_rust::aws::s3::endpoint::*;
+use aws_sdk_rust::aws::s3::s3client::http_client;
+use aws_sdk_rust::aws::common::credentials::{AwsCredentials, AwsCredentialsProvider, DefaultCredentialsProviderSync};
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+use aws_sdk_rust::aws::common::signature::SignedRequest;
+use aws_sdk_rust::aws::common::params::*;
+
+/// A raw S3 request.
+///
+/// ```subresources``` are the signed query values such as ```policy``` or ```uploadId```. V2 signs
+/// them as part of the path and V4 as query parameters. ```params``` are plain query parameters
+/// such as ```prefix``` or ```max-keys```.
+///
+#[derive(Debug, Default, Clone)]
+pub struct S3Request {
+    pub method: String,
+    pub bucket: String,
+    pub key: String,
+    pub subresources: Vec<(String, String)>,
+    pub params: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub content_type: Option<String>,
+    pub payload: Option<Vec<u8>>,
+}
+
+/// Status, headers and body of an S3 response.
+///
+#[derive(Debug, Default, Clone)]
+pub struct S3Response {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl S3Request {
+    pub fn new(method: &str, bucket: &str, key: &str) -> S3Request {
+        S3Request {
+            method: method.to_string(),
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            ..S3Request::default()
+        }
+    }
+}
+
+impl S3Response {
+    /// Header lookup ignoring case since servers differ in how they send header names.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers.iter().find(|&(key, _)| key.to_lowercase() == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn body_str(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+// Credentials for every direct request. The provider is built once per process and caches the
+// credentials until they expire so an instance profile is not asked again on each request.
+fn credentials() -> Result<AwsCredentials, S3Error> {
+    static INIT: Once = ONCE_INIT;
+    static mut PROVIDER: *const Result<DefaultCredentialsProviderSync, String> = 0 as *const _;
+
+    // PROVIDER is only written once (inside call_once) and is never freed.
+    let provider = unsafe {
+        INIT.call_once(|| {
+            let provider = DefaultCredentialsProviderSync::new(None).map_err(|e| format!("{}", e));
+            PROVIDER = Box::into_raw(Box::new(provider));
+        });
+        &*PROVIDER
+    };
+
+    match *provider {
+        Ok(ref provider) => provider.credentials().map_err(|e| S3Error::new(format!("{}", e))),
+        Err(ref e) => Err(S3Error::new(e.clone())),
+    }
+}
+
+/// Signs and sends the request. Any status outside of 2xx is returned as an ```S3Error``` built
+/// from the S3 error document.
+pub fn send(endpoint: &Endpoint, input: &S3Request) -> Result<S3Response, S3Error> {
+    let mut subresources = input.subresources.clone();
+    subresources.sort();
+
+    let mut path = format!("/{}", input.key);
+    let mut params = Params::new();
+
+    // NOTE: V4 - For sub-resources add them as params and not part of the path
+    // NOTE: V2 - For sub-resources add then as part of the path and not as params
+    if endpoint.signature == Signature::V2 && !subresources.is_empty() {
+        let query: Vec<String> = subresources.iter()
+            .map(|&(ref key, ref value)| if value.is_empty() { key.clone() } else { format!("{}={}", key, value) })
+            .collect();
+        path = format!("{}?{}", path, query.join("&"));
+    } else {
+        for &(ref key, ref value) in &subresources {
+            params.put(key, value);
+        }
+    }
+
+    for &(ref key, ref value) in &input.params {
+        params.put(key, value);
+    }
+
+    let hostname = match endpoint.hostname() {
+        Some(hostname) => hostname,
+        None => return Err(S3Error::new("Endpoint has no hostname")),
+    };
+
+    let mut request = SignedRequest::new(&input.method, "s3", endpoint.region, &input.bucket, &path, endpoint);
+
+    // NOTE: If the bucket name contains '.' then it must follow path vs virtual bucket
+    if input.bucket.is_empty() || input.bucket.contains('.') || !endpoint.is_bucket_virtual {
+        request.set_hostname(Some(hostname));
+    } else {
+        request.set_hostname(Some(format!("{}.{}", input.bucket, hostname)));
+    }
+
+    let has_params = !params.is_empty();
+    request.set_params(params);
+
+    for &(ref key, ref value) in &input.headers {
+        // Signing overwrites the Content-Type header with the request content type.
+        if key.to_lowercase() == "content-type" {
+            request.set_content_type(value.clone());
+        } else {
+            request.add_header(key, value);
+        }
+    }
+
+    if let Some(ref content_type) = input.content_type {
+        request.set_content_type(content_type.clone());
+    }
+
+    if let Some(ref payload) = input.payload {
+        request.set_payload(Some(payload));
+    }
+
+    let credentials = try!(credentials());
+
+    request.sign(&credentials);
+
+    // The dispatcher appends the query string right after an existing '?' so V2 sub-resources need
+    // a separator once signing is done.
+    if endpoint.signature == Signature::V2 && !subresources.is_empty() && has_params {
+        request.path = format!("{}&", request.path);
+    }
+
+    let url = match endpoint.endpoint {
+        Some(ref url) => url.clone(),
+        None => return Err(S3Error::new("Endpoint was not specified")),
+    };
+
+    let response = try!(http_client(endpoint.proxy.clone(), url)
+        .dispatch(&request)
+        .map_err(|e| S3Error::new(format!("{}", e))));
+
+    let body = if response.is_body {
+        response.body.into_bytes()
+    } else {
+        response.body_buffer
+    };
+
+    let output = S3Response {
+        status: response.status,
+        headers: response.headers,
+        body: body,
+    };
+
+    match output.status {
+        200...299 => Ok(output),
+        status => {
+            let body = output.body_str();
+            let mut aws = AWSError::default();
+            aws.code = xml_value(&body, "Code").unwrap_or(format!("{}", status));
+            aws.message = xml_value(&body, "Message").unwrap_or(String::new());
+            aws.request_id = xml_value(&body, "RequestId").unwrap_or(String::new());
+            aws.host_id = xml_value(&body, "HostId").unwrap_or(String::new());
+            aws.resource = xml_value(&body, "Resource").unwrap_or(String::new());
+            let message = format!("{} {}/{} failed with status {}", input.method, input.bucket, input.key, status);
+            Err(S3Error::with_aws(message, aws))
+        },
+    }
+}
+
+/// Returns the (unescaped) text of the first ```<tag>``` element.
+pub fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag).into_iter().next().map(|value| xml_unescape(value))
+}
+
+/// Returns the raw inner XML of every ```<tag>``` element. S3 responses do not nest elements with
+/// the same name so a simple scan is enough.
+pub fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Make sure this is <tag> or <tag attr=...> and not <tagSomethingElse>
+        match after.chars().next() {
+            Some('>') | Some(' ') | Some('/') => {},
+            _ => {
+                rest = after;
+                continue;
+            },
+        }
+
+        let tag_end = match after.find('>') {
+            Some(tag_end) => tag_end,
+            None => break,
+        };
+
+        // <tag/> is an empty element
+        if after[..tag_end].ends_with('/') {
+            elements.push("");
+            rest = &after[tag_end + 1..];
+            continue;
+        }
+
+        let content = &after[tag_end + 1..];
+        match content.find(&close) {
+            Some(end) => {
+                elements.push(&content[..end]);
+                rest = &content[end + close.len()..];
+            },
+            None => break,
+        }
+    }
+
+    elements
+}
+
+pub fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub fn xml_unescape(value: &str) -> String {
+    value.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// One object from a bucket listing.
+///
+#[derive(Debug, Default, Clone, RustcEncodable)]
+pub struct ObjectEntry {
+    pub key: String,
+    pub size: u64,
+    pub e_tag: String,
+    pub last_modified: String,
+    pub storage_class: String,
+}
+
+/// One page of a bucket listing. ```next_marker``` is where the next page starts when the listing
+/// is truncated (the marker for V1 and the continuation token for V2).
+///
+#[derive(Debug, Default, Clone, RustcEncodable)]
+pub struct ObjectPage {
+    pub objects: Vec<ObjectEntry>,
+    pub common_prefixes: Vec<String>,
+    pub is_truncated: bool,
+    pub next_marker: String,
+}
+
+/// Lists a single page of objects starting at marker. list_version 2 uses ListObjectsV2 where
+/// marker is the continuation token from the previous page. Anything else uses ListObjects V1.
+pub fn list_objects_page(endpoint: &Endpoint, bucket: &str, prefix: &str, delimiter: &str, marker: &str,
+                         max_keys: Option<u64>, list_version: u16)
+                         -> Result<ObjectPage, S3Error> {
+    let mut request = S3Request::new("GET", bucket, "");
+    if list_version == 2 {
+        request.params.push(("list-type".to_string(), "2".to_string()));
+    }
+    if !prefix.is_empty() {
+        request.params.push(("prefix".to_string(), prefix.to_string()));
+    }
+    if !delimiter.is_empty() {
+        request.params.push(("delimiter".to_string(), delimiter.to_string()));
+    }
+    if !marker.is_empty() {
+        let name = if list_version == 2 { "continuation-token" } else { "marker" };
+        request.params.push((name.to_string(), marker.to_string()));
+    }
+    if let Some(max_keys) = max_keys {
+        request.params.push(("max-keys".to_string(), format!("{}", max_keys)));
+    }
+
+    let body = try!(send(endpoint, &request)).body_str();
+    let mut page = ObjectPage::default();
+
+    for contents in xml_elements(&body, "Contents") {
+        page.objects.push(ObjectEntry {
+            key: xml_value(contents, "Key").unwrap_or(String::new()),
+            size: xml_value(contents, "Size").and_then(|size| size.parse::<u64>().ok()).unwrap_or(0),
+            e_tag: xml_value(contents, "ETag").unwrap_or(String::new()),
+            last_modified: xml_value(contents, "LastModified").unwrap_or(String::new()),
+            storage_class: xml_value(contents, "StorageClass").unwrap_or(String::new()),
+        });
+    }
+
+    for common_prefix in xml_elements(&body, "CommonPrefixes") {
+        if let Some(prefix) = xml_value(common_prefix, "Prefix") {
+            page.common_prefixes.push(prefix);
+        }
+    }
+
+    page.is_truncated = xml_value(&body, "IsTruncated").map(|truncated| truncated == "true").unwrap_or(false);
+
+    // NextMarker only comes back when a delimiter is used. Otherwise the last key is the marker.
+    if page.is_truncated && list_version == 2 {
+        page.next_marker = xml_value(&body, "NextContinuationToken").unwrap_or(String::new());
+    } else if page.is_truncated {
+        page.next_marker = match xml_value(&body, "NextMarker") {
+            Some(ref next_marker) if !next_marker.is_empty() => next_marker.clone(),
+            _ => {
+                let last_key = page.objects.last().map(|object| object.key.clone()).unwrap_or(String::new());
+                let last_prefix = page.common_prefixes.last().cloned().unwrap_or(String::new());
+                if last_key > last_prefix { last_key } else { last_prefix }
+            },
+        };
+    }
+
+    Ok(page)
+}
+
+/// Lists every object under prefix, following markers until the listing is complete.
+pub fn list_all_objects(endpoint: &Endpoint, bucket: &str, prefix: &str) -> Result<Vec<ObjectEntry>, S3Error> {
+    let mut objects = Vec::new();
+    let mut marker = String::new();
+
+    loop {
+        let page = try!(list_objects_page(endpoint, bucket, prefix, "", &marker, None, 1));
+        objects.extend(page.objects);
+
+        if !page.is_truncated || page.next_marker.is_empty() {
+            break;
+        }
+        marker = page.next_marker;
+    }
+
+    Ok(objects)
+}
+
+/// A key DeleteObjects could not delete.
+///
+#[derive(Debug, Default, Clone, RustcEncodable)]
+pub struct DeleteError {
+    pub key: String,
+    pub version_id: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Deletes up to 1000 objects in one Multi-Object Delete request. Each object is a (key, version
+/// id) pair where an empty version id means the current version. Quiet mode is used so only the
+/// keys that failed come back.
+pub fn delete_objects(endpoint: &Endpoint, bucket: &str, objects: &[(String, String)])
+                      -> Result<Vec<DeleteError>, S3Error> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Delete><Quiet>true</Quiet>");
+    for &(ref key, ref version_id) in objects {
+        xml.push_str(&format!("<Object><Key>{}</Key>", xml_escape(key)));
+        if !version_id.is_empty() {
+            xml.push_str(&format!("<VersionId>{}</VersionId>", xml_escape(version_id)));
+        }
+        xml.push_str("</Object>");
+    }
+    xml.push_str("</Delete>");
+
+    let payload = xml.into_bytes();
+    let mut request = S3Request::new("POST", bucket, "");
+    request.subresources.push(("delete".to_string(), String::new()));
+    // Content-MD5 is required for this request.
+    request.headers.push(("Content-MD5".to_string(), md5::compute(&payload).to_base64(STANDARD)));
+    request.content_type = Some("application/xml".to_string());
+    request.payload = Some(payload);
+
+    let body = try!(send(endpoint, &request)).body_str();
+
+    Ok(xml_elements(&body, "Error")
+        .into_iter()
+        .map(|error| {
+            DeleteError {
+                key: xml_value(error, "Key").unwrap_or(String::new()),
+                version_id: xml_value(error, "VersionId").unwrap_or(String::new()),
+                code: xml_value(error, "Code").unwrap_or(String::new()),
+                message: xml_value(error, "Message").unwrap_or(String::new()),
+            }
+        })
+        .collect())
+}
+
+/// One object version or delete marker from a version listing.
+///
+#[derive(Debug, Default, Clone, RustcEncodable)]
+pub struct VersionEntry {
+    pub key: String,
+    pub version_id: String,
+    pub is_delete_marker: bool,
+}
+
+/// Lists every object version and delete marker under prefix, following the key and version id
+/// markers until the listing is complete.
+pub fn list_all_versions(endpoint: &Endpoint, bucket: &str, prefix: &str) -> Result<Vec<VersionEntry>, S3Error> {
+    let mut versions = Vec::new();
+    let mut key_marker = String::new();
+    let mut version_id_marker = String::new();
+
+    loop {
+        let mut request = S3Request::new("GET", bucket, "");
+        request.subresources.push(("versions".to_string(), String::new()));
+        if !prefix.is_empty() {
+            request.params.push(("prefix".to_string(), prefix.to_string()));
+        }
+        if !key_marker.is_empty() {
+            request.params.push(("key-marker".to_string(), key_marker.clone()));
+        }
+        if !version_id_marker.is_empty() {
+            request.params.push(("version-id-marker".to_string(), version_id_marker.clone()));
+        }
+
+        let body = try!(send(endpoint, &request)).body_str();
+
+        for (tag, is_delete_marker) in vec![("Version", false), ("DeleteMarker", true)] {
+            for version in xml_elements(&body, tag) {
+                versions.push(VersionEntry {
+                    key: xml_value(version, "Key").unwrap_or(String::new()),
+                    version_id: xml_value(version, "VersionId").unwrap_or(String::new()),
+                    is_delete_marker: is_delete_marker,
+                });
+            }
+        }
+
+        let is_truncated = xml_value(&body, "IsTruncated").map(|truncated| truncated == "true").unwrap_or(false);
+        key_marker = xml_value(&body, "NextKeyMarker").unwrap_or(String::new());
+        version_id_marker = xml_value(&body, "NextVersionIdMarker").unwrap_or(String::new());
+
+        if !is_truncated || key_marker.is_empty() {
+            break;
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Lists every in-progress multipart upload under prefix as (key, upload id) pairs.
+pub fn list_all_uploads(endpoint: &Endpoint, bucket: &str, prefix: &str) -> Result<Vec<(String, String)>, S3Error> {
+    let mut uploads = Vec::new();
+    let mut key_marker = String::new();
+    let mut upload_id_marker = String::new();
+
+    loop {
+        let mut request = S3Request::new("GET", bucket, "");
+        request.subresources.push(("uploads".to_string(), String::new()));
+        if !prefix.is_empty() {
+            request.params.push(("prefix".to_string(), prefix.to_string()));
+        }
+        if !key_marker.is_empty() {
+            request.params.push(("key-marker".to_string(), key_marker.clone()));
+        }
+        if !upload_id_marker.is_empty() {
+            request.params.push(("upload-id-marker".to_string(), upload_id_marker.clone()));
+        }
+
+        let body = try!(send(endpoint, &request)).body_str();
+
+        for upload in xml_elements(&body, "Upload") {
+            uploads.push((xml_value(upload, "Key").unwrap_or(String::new()),
+                          xml_value(upload, "UploadId").unwrap_or(String::new())));
+        }
+
+        let is_truncated = xml_value(&body, "IsTruncated").map(|truncated| truncated == "true").unwrap_or(false);
+        key_marker = xml_value(&body, "NextKeyMarker").unwrap_or(String::new());
+        upload_id_marker = xml_value(&body, "NextUploadIdMarker").unwrap_or(String::new());
+
+        if !is_truncated || key_marker.is_empty() {
+            break;
+        }
+    }
+
+    Ok(uploads)
+}
+
+/// One uploaded part of a multipart upload.
+///
+#[derive(Debug, Default, Clone)]
+pub struct PartEntry {
+    pub part_number: u64,
+    pub size: u64,
+    pub e_tag: String,
+}
+
+/// Lists every part uploaded so far, following the part number marker past the 1000 parts a
+/// single ListParts returns.
+pub fn list_all_parts(endpoint: &Endpoint, bucket: &str, key: &str, upload_id: &str)
+                      -> Result<Vec<PartEntry>, S3Error> {
+    let mut parts = Vec::new();
+    let mut part_number_marker = String::new();
+
+    loop {
+        let mut request = S3Request::new("GET", bucket, key);
+        request.subresources.push(("uploadId".to_string(), upload_id.to_string()));
+        if !part_number_marker.is_empty() {
+            request.params.push(("part-number-marker".to_string(), part_number_marker.clone()));
+        }
+
+        let body = try!(send(endpoint, &request)).body_str();
+
+        for part in xml_elements(&body, "Part") {
+            parts.push(PartEntry {
+                part_number: xml_value(part, "PartNumber").and_then(|number| number.parse().ok()).unwrap_or(0),
+                size: xml_value(part, "Size").and_then(|size| size.parse().ok()).unwrap_or(0),
+                e_tag: xml_value(part, "ETag").unwrap_or(String::new()),
+            });
+        }
+
+        let is_truncated = xml_value(&body, "IsTruncated").map(|truncated| truncated == "true").unwrap_or(false);
+        part_number_marker = xml_value(&body, "NextPartNumberMarker").unwrap_or(String::new());
+
+        if !is_truncated || part_number_marker.is_empty() {
+            break;
+        }
+    }
+
+    Ok(parts)
+}
+
+/// Starts a multipart upload sending headers (ACL, grants etc.) that apply to the final object
+/// and returns the upload id.
+pub fn create_multipart_upload(endpoint: &Endpoint, bucket: &str, key: &str, headers: &[(String, String)])
+                               -> Result<String, S3Error> {
+    let mut request = S3Request::new("POST", bucket, key);
+    request.subresources.push(("uploads".to_string(), String::new()));
+    request.headers.extend(headers.iter().cloned());
+
+    let body = try!(send(endpoint, &request)).body_str();
+
+    match xml_value(&body, "UploadId") {
+        Some(upload_id) => Ok(upload_id),
+        None => Err(S3Error::new(format!("No UploadId in the response for {}/{}", bucket, key))),
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2016 LambdaStack All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! One-way sync from a local directory or bucket prefix to another. Only the files/objects that
+//! are missing or differ are transferred and with ```--delete``` anything in the destination that
+//! is not in the source is removed.
+
+use std::fs;
+use std::path::PathBuf;
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
+use clap::ArgMatches;
+
+use aws_sdk_rust::aws::errors::s3::S3Error;
+use aws_sdk_rust::aws::common::credentials::AwsCredentialsProvider;
+use aws_sdk_rust::aws::common::request::DispatchSignedRequest;
+
+use common::*;
+use commands::{cmd_get, cmd_put, copy_object, delete_object, is_md5_e_tag, is_transfer_state_file, prefix_dir};
+use s3request;
+use s3request::S3Request;
+use filter::{key_filter, KeyFilter};
+
+use Client;
+use OutputFormat;
+
+/// One side of a sync.
+#[derive(Debug, Clone, PartialEq)]
+enum Location {
+    Local(PathBuf),
+    S3(String, String),
+}
+
+/// What is compared between the two sides. ```modified``` is in seconds since the epoch and
+/// ```e_tag``` is only known for objects.
+#[derive(Debug, Clone)]
+struct SyncEntry {
+    size: u64,
+    modified: i64,
+    e_tag: String,
+}
+
+/// Sync
+pub fn sync<P, D>(matches: &ArgMatches, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let src = matches.value_of("src").unwrap_or("");
+    let dst = matches.value_of("dst").unwrap_or("");
+    let is_delete = matches.is_present("delete");
+    let is_dry_run = matches.is_present("dry-run");
+    let is_checksum = matches.is_present("checksum");
+
+    if src.is_empty() || dst.is_empty() {
+        let error = format!("Source and destination must be specified");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    let src = location(src);
+    let dst = location(dst);
+
+    if let (&Location::Local(_), &Location::Local(_)) = (&src, &dst) {
+        let error = format!("Source or destination must be s3://<bucket>");
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    // Filtered out keys are left alone on both sides (so --delete does not remove them either).
+    let filter = try!(key_filter(matches, client));
+    let src_entries = try!(entries(&src, &filter, client));
+    let dst_entries = try!(entries(&dst, &filter, client));
+
+    let mut transferred: usize = 0;
+    let mut deleted: usize = 0;
+    let mut failed: usize = 0;
+
+    for (relative, entry) in &src_entries {
+        let is_needed = match dst_entries.get(relative) {
+            Some(dst_entry) => is_changed(&src, &dst, relative, entry, dst_entry, is_checksum, client),
+            None => true,
+        };
+
+        if !is_needed {
+            continue;
+        }
+
+        let action = match (&src, &dst) {
+            (&Location::Local(_), _) => "upload",
+            (_, &Location::Local(_)) => "download",
+            _ => "copy",
+        };
+
+        report(&format!("{}: {} to {}", action, display(&src, relative), display(&dst, relative)),
+               is_dry_run,
+               client);
+
+        if is_dry_run {
+            continue;
+        }
+
+        match transfer(&src, &dst, relative, client) {
+            Ok(_) => transferred += 1,
+            Err(_) => failed += 1,
+        }
+    }
+
+    if is_delete {
+        for relative in dst_entries.keys().filter(|relative| !src_entries.contains_key(*relative)) {
+            report(&format!("delete: {}", display(&dst, relative)), is_dry_run, client);
+
+            if is_dry_run {
+                continue;
+            }
+
+            let result = match dst {
+                Location::Local(ref root) => {
+                    fs::remove_file(root.join(relative)).map_err(|e| S3Error::new(format!("{}", e)))
+                },
+                Location::S3(ref bucket, ref prefix) => {
+                    delete_object(bucket, &format!("{}{}", prefix_dir(prefix), relative), "", None, client)
+                },
+            };
+
+            match result {
+                Ok(_) => deleted += 1,
+                Err(e) => {
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", e);
+                    failed += 1;
+                },
+            }
+        }
+    }
+
+    if failed > 0 {
+        let error = format!("Sync: {} transferred, {} deleted, {} failed", transferred, deleted, failed);
+        println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+        return Err(S3Error::new(error));
+    }
+
+    if !is_dry_run && (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet,
+                             client.output.color,
+                             "Sync: {} transferred, {} deleted",
+                             transferred,
+                             deleted);
+    }
+
+    Ok(())
+}
+
+fn location(arg: &str) -> Location {
+    if arg.starts_with("s3://") {
+        let path = &arg[5..];
+        match path.find('/') {
+            Some(index) => Location::S3(path[..index].to_string(), path[index + 1..].to_string()),
+            None => Location::S3(path.trim().to_string(), String::new()),
+        }
+    } else {
+        Location::Local(PathBuf::from(arg))
+    }
+}
+
+fn display(location: &Location, relative: &str) -> String {
+    match *location {
+        Location::Local(ref root) => {
+            match local_path(root, relative) {
+                Some(path) => path.to_string_lossy().into_owned(),
+                None => format!("{} (outside of {})", relative, root.display()),
+            }
+        },
+        Location::S3(ref bucket, ref prefix) => format!("s3://{}/{}{}", bucket, prefix_dir(prefix), relative),
+    }
+}
+
+// Prints a planned or performed action.
+fn report<P, D>(action: &str, is_dry_run: bool, client: &Client<P, D>)
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if (client.output.format != OutputFormat::None) && (client.output.format != OutputFormat::NoneAll) {
+        println_color_quiet!(client.is_quiet,
+                             client.output.color,
+                             "{}{}",
+                             if is_dry_run { "(dry-run) " } else { "" },
+                             action);
+    }
+}
+
+// Everything under the location that passes the filter keyed by its path relative to the location.
+fn entries<P, D>(location: &Location, filter: &KeyFilter, client: &Client<P, D>)
+                  -> Result<BTreeMap<String, SyncEntry>, S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    let mut entries = BTreeMap::new();
+
+    match *location {
+        Location::Local(ref root) => {
+            // A destination directory that does not exist yet is simply empty.
+            if !root.exists() {
+                return Ok(entries);
+            }
+
+            let files = match walk_files(root) {
+                Ok(files) => files,
+                Err(e) => {
+                    let error = format!("Error reading directory {}: {}", root.display(), e);
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                    return Err(S3Error::new(error));
+                },
+            };
+
+            let files = files.iter()
+                .filter(|file| !is_transfer_state_file(file) && filter.is_match(&relative_key(root, file)));
+
+            for file in files {
+                if let Ok(metadata) = fs::metadata(file) {
+                    entries.insert(relative_key(root, file),
+                                   SyncEntry {
+                                       size: metadata.len(),
+                                       modified: file_modified(&metadata) as i64,
+                                       e_tag: String::new(),
+                                   });
+                }
+            }
+        },
+        Location::S3(ref bucket, ref prefix) => {
+            let prefix = prefix_dir(prefix);
+            let objects = match s3request::list_all_objects(client.s3client.endpoint(), bucket, &prefix) {
+                Ok(objects) => objects,
+                Err(e) => {
+                    let error = format!("{:#?}", e);
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                    return Err(S3Error::new(error));
+                },
+            };
+
+            // Keys ending in '/' are folder placeholders.
+            let objects = objects.into_iter()
+                .filter(|object| !object.key.ends_with('/') && filter.is_match(&object.key[prefix.len()..]));
+
+            for object in objects {
+                let modified = DateTime::parse_from_rfc3339(&object.last_modified)
+                    .map(|modified| modified.timestamp())
+                    .unwrap_or(0);
+                entries.insert(object.key[prefix.len()..].to_string(),
+                               SyncEntry {
+                                   size: object.size,
+                                   modified: modified,
+                                   e_tag: object.e_tag.trim_matches('"').to_string(),
+                               });
+            }
+        },
+    }
+
+    Ok(entries)
+}
+
+// Size always counts. With --checksum the MD5s are compared when both are known (multipart,
+// SSE-KMS and SSE-C ETags are not an MD5) otherwise a newer source wins.
+fn is_changed<P, D>(src: &Location, dst: &Location, relative: &str, src_entry: &SyncEntry, dst_entry: &SyncEntry,
+                    is_checksum: bool, client: &Client<P, D>)
+                    -> bool
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    if src_entry.size != dst_entry.size {
+        return true;
+    }
+
+    if is_checksum {
+        if let Some(src_md5) = md5_of(src, relative, src_entry, client) {
+            if let Some(dst_md5) = md5_of(dst, relative, dst_entry, client) {
+                return src_md5 != dst_md5;
+            }
+        }
+    }
+
+    src_entry.modified > dst_entry.modified
+}
+
+// The listing does not say how an object is encrypted so it is HEADed before its ETag is trusted.
+// A failed HEAD is treated as unknown.
+fn md5_of<P, D>(location: &Location, relative: &str, entry: &SyncEntry, client: &Client<P, D>) -> Option<String>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match *location {
+        Location::Local(ref root) => file_md5(&root.join(relative)).ok(),
+        Location::S3(ref bucket, ref prefix) => {
+            if entry.e_tag.is_empty() || entry.e_tag.contains('-') {
+                return None;
+            }
+
+            let mut request = S3Request::new("HEAD", bucket, &format!("{}{}", prefix_dir(prefix), relative));
+            request.headers = client.sse_customer_headers.clone();
+            match s3request::send(client.s3client.endpoint(), &request) {
+                Ok(ref response) if is_md5_e_tag(response, client) => Some(entry.e_tag.clone()),
+                _ => None,
+            }
+        },
+    }
+}
+
+fn transfer<P, D>(src: &Location, dst: &Location, relative: &str, client: &Client<P, D>) -> Result<(), S3Error>
+    where P: AwsCredentialsProvider,
+          D: DispatchSignedRequest,
+{
+    match (src, dst) {
+        (&Location::Local(ref root), &Location::S3(ref bucket, ref prefix)) => {
+            let key = format!("{}{}", prefix_dir(prefix), relative);
+            cmd_put(bucket, &key, &root.join(relative).to_string_lossy(), 0, client)
+        },
+        (&Location::S3(ref bucket, ref prefix), &Location::Local(ref root)) => {
+            let path = match local_path(root, relative) {
+                Some(path) => path,
+                None => {
+                    let error = format!("Skipping {}: the key would be written outside of {}",
+                                        display(src, relative),
+                                        root.display());
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                    return Err(S3Error::new(error));
+                },
+            };
+            if let Some(parent) = path.parent() {
+                if let Err(e) = fs::create_dir_all(parent) {
+                    let error = format!("Error creating {}: {}", parent.display(), e);
+                    println_color_quiet!(client.is_quiet, client.error.color, "{}", error);
+                    return Err(S3Error::new(error));
+                }
+            }
+            let key = format!("{}{}", prefix_dir(prefix), relative);
+            cmd_get(bucket, &key, &path.to_string_lossy(), false, false, client)
+        },
+        (&Location::S3(ref src_bucket, ref src_prefix), &Location::S3(ref bucket, ref prefix)) => {
+            copy_object(src_bucket,
+                        &format!("{}{}", prefix_dir(src_prefix), relative),
+                        bucket,
+                        &format!("{}{}", prefix_dir(prefix), relative),
+                        client)
+        },
+        _ => Err(S3Error::new("Source or destination must be s3://<bucket>")),
+    }
+}